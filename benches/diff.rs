@@ -0,0 +1,114 @@
+//! Benchmarks for computing diffs between the local and remote code of a task.
+//!
+//! The inputs are the largest tasks in the repository, compared against a copy in which every
+//! tenth line has been changed. `coverage --diff` renders the changesets of `meta::diff`, and the
+//! `difference` crate that it used before is kept as a baseline to compare them against.
+//!
+//! Run with `cargo bench --bench diff`. Each result is printed as `ns/iter`, one benchmark per
+//! line, so CI logs can be compared across runs.
+
+#![feature(test)]
+
+extern crate difference;
+extern crate meta;
+extern crate test;
+
+use difference::Changeset;
+use meta::diff;
+use test::Bencher;
+
+/// The largest program in the repository.
+const S_EXPRESSIONS: &str = include_str!("../tasks/s-expressions/src/main.rs");
+
+/// The largest library in the repository.
+const AVL_TREE: &str = include_str!("../tasks/avl-tree/src/lib.rs");
+
+/// Returns a copy of the code in which every tenth line is changed.
+fn modified(code: &str) -> String {
+    code.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i % 10 == 0 {
+                format!("{} // changed", line)
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn bench_difference(b: &mut Bencher, code: &str) {
+    let other = modified(code);
+    b.iter(|| Changeset::new(code, &other, "\n"));
+}
+
+fn bench_changeset(b: &mut Bencher, code: &str) {
+    let other = modified(code);
+    b.iter(|| diff::changeset(code, &other));
+}
+
+fn bench_streaming_changeset(b: &mut Bencher, code: &str) {
+    let other = modified(code);
+    b.iter(|| diff::streaming_changeset(code, &other));
+}
+
+fn bench_changed_lines(b: &mut Bencher, code: &str) {
+    let other = modified(code);
+    b.iter(|| diff::changed_lines(code, &other));
+}
+
+fn bench_unified_diff(b: &mut Bencher, code: &str) {
+    let other = modified(code);
+    b.iter(|| diff::unified_diff(code, &other, "wiki", "local", 3));
+}
+
+#[bench]
+fn difference_s_expressions(b: &mut Bencher) {
+    bench_difference(b, S_EXPRESSIONS);
+}
+
+#[bench]
+fn difference_avl_tree(b: &mut Bencher) {
+    bench_difference(b, AVL_TREE);
+}
+
+#[bench]
+fn changeset_s_expressions(b: &mut Bencher) {
+    bench_changeset(b, S_EXPRESSIONS);
+}
+
+#[bench]
+fn changeset_avl_tree(b: &mut Bencher) {
+    bench_changeset(b, AVL_TREE);
+}
+
+#[bench]
+fn streaming_changeset_s_expressions(b: &mut Bencher) {
+    bench_streaming_changeset(b, S_EXPRESSIONS);
+}
+
+#[bench]
+fn streaming_changeset_avl_tree(b: &mut Bencher) {
+    bench_streaming_changeset(b, AVL_TREE);
+}
+
+#[bench]
+fn changed_lines_s_expressions(b: &mut Bencher) {
+    bench_changed_lines(b, S_EXPRESSIONS);
+}
+
+#[bench]
+fn changed_lines_avl_tree(b: &mut Bencher) {
+    bench_changed_lines(b, AVL_TREE);
+}
+
+#[bench]
+fn unified_diff_s_expressions(b: &mut Bencher) {
+    bench_unified_diff(b, S_EXPRESSIONS);
+}
+
+#[bench]
+fn unified_diff_avl_tree(b: &mut Bencher) {
+    bench_unified_diff(b, AVL_TREE);
+}
@@ -0,0 +1,10 @@
+// TODO: Handle negative numbers.
+fn parse(input: &str) -> u32 {
+    input.parse().unwrap() // FIXME: Report invalid input.
+}
+
+fn main() {
+    let todo = "not a marker";
+    println!("{} {}", parse("42"), todo);
+    unimplemented!()
+}
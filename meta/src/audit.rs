@@ -0,0 +1,208 @@
+//! Comparing the tasks in the repository against the list of tasks on the wiki.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use client::{self, WikiClient};
+use local::{self, LocalTask};
+use remote;
+use Result;
+
+/// The differences between the tasks in the repository and the tasks on the wiki.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Audit {
+    /// The programming tasks on the wiki that are neither implemented locally nor omitted, sorted
+    /// by title.
+    pub unimplemented: Vec<String>,
+
+    /// The local tasks that are no longer listed on the wiki, neither as programming tasks nor as
+    /// draft tasks, sorted by title.
+    pub missing_on_wiki: Vec<String>,
+}
+
+impl Audit {
+    /// True if the repository and the wiki list the same tasks.
+    pub fn is_empty(&self) -> bool {
+        self.unimplemented.is_empty() && self.missing_on_wiki.is_empty()
+    }
+}
+
+/// Requests every programming task and draft task from the wiki, and compares them against the
+/// titles of the local tasks.
+///
+/// Unlike `TaskIndex::create`, this succeeds if some local tasks can't be found on the wiki, so it
+/// can be used to find them.
+pub fn audit(
+    client: &WikiClient,
+    local_titles: &BTreeSet<String>,
+    omitted_titles: &BTreeSet<String>,
+) -> Result<Audit> {
+    let members = |category| {
+        remote::category_members(category, |request| client::get_json(client, request)).map(
+            |titles| {
+                titles
+                    .iter()
+                    .map(|title| remote::decode_title(title))
+                    .collect::<BTreeSet<_>>()
+            },
+        )
+    };
+
+    let tasks = members("Programming Tasks")?;
+    let draft_tasks = members("Draft Programming Tasks")?;
+
+    let unimplemented = tasks
+        .iter()
+        .filter(|title| !local_titles.contains(*title) && !omitted_titles.contains(*title))
+        .cloned()
+        .collect();
+
+    let missing_on_wiki = local_titles
+        .iter()
+        .filter(|title| !tasks.contains(*title) && !draft_tasks.contains(*title))
+        .cloned()
+        .collect();
+
+    Ok(Audit {
+        unimplemented,
+        missing_on_wiki,
+    })
+}
+
+/// A local task whose page was renamed on the wiki, so that its title redirects to another page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Rename {
+    /// The title of the local task, as in its manifest.
+    pub title: String,
+
+    /// The title that the wiki redirects to.
+    pub new_title: String,
+
+    /// The directory of the crate, relative to the repository root.
+    pub dir: PathBuf,
+
+    /// The directory derived from the new title, relative to the repository root.
+    pub new_dir: PathBuf,
+}
+
+/// Asks the wiki which of the given local tasks were renamed, and returns the directory that each
+/// of them should be moved to, sorted by title. `workspace_root` is the directory that contains
+/// the workspace manifest.
+///
+/// Like `audit`, this doesn't need a `TaskIndex`, which can't be created while the old titles are
+/// in the repository.
+pub fn renames(
+    client: &WikiClient,
+    tasks: &[LocalTask],
+    workspace_root: &Path,
+) -> Result<Vec<Rename>> {
+    let titles = tasks
+        .iter()
+        .map(|task| task.title.clone())
+        .collect::<Vec<_>>();
+    let targets = remote::redirect_targets(&titles, |request| client::get_json(client, request))?;
+
+    let mut renames = vec![];
+
+    for task in tasks {
+        if let Some(new_title) = targets.get(&task.title) {
+            let dir = task.manifest_path.parent().unwrap();
+            let dir = dir.strip_prefix(workspace_root).unwrap_or(dir);
+
+            renames.push(Rename {
+                title: task.title.clone(),
+                new_title: new_title.clone(),
+                dir: dir.to_owned(),
+                new_dir: local::task_dir(new_title),
+            });
+        }
+    }
+
+    renames.sort_by(|a, b| a.title.cmp(&b.title));
+    Ok(renames)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+    use std::path::Path;
+
+    use reqwest::Url;
+
+    use super::{audit, renames, Rename};
+    use local::{LocalTask, SourceFormats};
+    use MockWikiClient;
+
+    #[test]
+    fn gaps() {
+        let client = MockWikiClient::new()
+            .with_response(
+                &[("cmtitle", "Category:Programming Tasks")],
+                r#"{
+                    "query": {
+                        "categorymembers": [
+                            { "ns": 0, "title": "100 doors" },
+                            { "ns": 0, "title": "A+B" },
+                            { "ns": 0, "title": "Bitmap" },
+                            { "ns": 0, "title": "Zig-zag matrix" }
+                        ]
+                    }
+                }"#,
+            )
+            .with_response(
+                &[("cmtitle", "Category:Draft Programming Tasks")],
+                r#"{ "query": { "categorymembers": [ { "ns": 0, "title": "Draft" } ] } }"#,
+            );
+
+        let local_titles = ["100 doors", "Draft", "Removed task"]
+            .iter()
+            .map(|title| String::from(*title))
+            .collect::<BTreeSet<_>>();
+        let omitted_titles = Some(String::from("Bitmap")).into_iter().collect();
+
+        let audit = audit(&client, &local_titles, &omitted_titles).unwrap();
+        assert_eq!(audit.unimplemented, vec!["A+B", "Zig-zag matrix"]);
+        assert_eq!(audit.missing_on_wiki, vec!["Removed task"]);
+        assert!(!audit.is_empty());
+    }
+
+    #[test]
+    fn renamed_tasks() {
+        let client = MockWikiClient::new().with_response(
+            &[("redirects", "1")],
+            r#"{
+                "query": {
+                    "redirects": [
+                        { "from": "Quicksort", "to": "Sorting algorithms/Quicksort" }
+                    ],
+                    "pages": {}
+                }
+            }"#,
+        );
+
+        let task = |title: &str, dir: &str| LocalTask {
+            package_name: String::from("task"),
+            manifest_path: Path::new("/repo").join(dir).join("Cargo.toml"),
+            source: HashSet::new(),
+            formats: SourceFormats::default(),
+            dependencies: HashSet::new(),
+            url: Url::parse("http://rosettacode.org/wiki/").unwrap(),
+            title: title.to_owned(),
+        };
+        let tasks = vec![
+            task("Quicksort", "tasks/quicksort"),
+            task("100 doors", "tasks/100-doors"),
+        ];
+
+        let renames = renames(&client, &tasks, Path::new("/repo")).unwrap();
+        assert_eq!(
+            renames,
+            vec![Rename {
+                title: String::from("Quicksort"),
+                new_title: String::from("Sorting algorithms/Quicksort"),
+                dir: Path::new("tasks/quicksort").to_owned(),
+                new_dir: Path::new("tasks/sorting-algorithms/quicksort").to_owned(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,989 @@
+//! Abstractions over the HTTP requests made to the Rosetta Code wiki.
+
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::Headers;
+use reqwest::{self, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde_json::{self, Value};
+
+use error::Error;
+use remote::API_URL;
+use Result;
+
+/// A client that can perform requests against the MediaWiki API.
+///
+/// Abstracting the client allows the parsing and selection logic of the crate to be tested
+/// without access to the network.
+pub trait WikiClient {
+    /// Performs a GET request for the given URL and returns the body of the response.
+    fn get(&self, url: &Url) -> Result<String>;
+}
+
+impl<C: ?Sized> WikiClient for Rc<C>
+where
+    C: WikiClient,
+{
+    fn get(&self, url: &Url) -> Result<String> {
+        (**self).get(url)
+    }
+}
+
+impl<C: ?Sized> WikiClient for Box<C>
+where
+    C: WikiClient,
+{
+    fn get(&self, url: &Url) -> Result<String> {
+        (**self).get(url)
+    }
+}
+
+/// Performs a request with a `WikiClient` and deserializes the JSON response.
+pub fn get_json<T>(client: &WikiClient, url: &Url) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    Ok(serde_json::from_str(&client.get(url)?)?)
+}
+
+/// The number of requests that an `HttpClient` sends to the wiki at the same time by default.
+pub const DEFAULT_WIKI_CONCURRENCY: usize = 2;
+
+/// The number of requests that an `HttpClient` sends to any other host at the same time.
+pub const DEFAULT_HOST_CONCURRENCY: usize = 4;
+
+/// Limits how many requests can be sent to each host at the same time, so that no single server
+/// is overwhelmed when requests are sent from several threads.
+#[derive(Debug)]
+pub struct HostLimits {
+    default_limit: usize,
+    limits: HashMap<String, usize>,
+    in_flight: Mutex<HashMap<String, usize>>,
+    released: Condvar,
+}
+
+impl HostLimits {
+    /// Creates limits that allow `default_limit` requests at the same time to every host.
+    pub fn new(default_limit: usize) -> HostLimits {
+        HostLimits {
+            default_limit,
+            limits: HashMap::new(),
+            in_flight: Mutex::new(HashMap::new()),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Allows `limit` requests at the same time to `host`, instead of the default limit.
+    pub fn with_limit(mut self, host: &str, limit: usize) -> HostLimits {
+        self.limits.insert(host.to_owned(), limit);
+        self
+    }
+
+    /// Returns the number of requests that can be sent to a host at the same time. The limit is
+    /// at least one, so that requests can always be sent.
+    pub fn limit(&self, host: &str) -> usize {
+        cmp::max(
+            1,
+            self.limits.get(host).cloned().unwrap_or(self.default_limit),
+        )
+    }
+
+    /// Waits until a request can be sent to the host of `url`. The request counts against the
+    /// limit of the host until the returned permit is dropped.
+    pub fn acquire(&self, url: &Url) -> HostPermit {
+        let host = url.host_str().unwrap_or("").to_owned();
+        let limit = self.limit(&host);
+
+        let mut in_flight = self.in_flight.lock().unwrap();
+        while in_flight.get(&host).cloned().unwrap_or(0) >= limit {
+            in_flight = self.released.wait(in_flight).unwrap();
+        }
+        *in_flight.entry(host.clone()).or_insert(0) += 1;
+
+        HostPermit { limits: self, host }
+    }
+}
+
+/// Allows a request to be sent to a host, as returned by `HostLimits::acquire`.
+#[derive(Debug)]
+pub struct HostPermit<'a> {
+    limits: &'a HostLimits,
+    host: String,
+}
+
+impl<'a> Drop for HostPermit<'a> {
+    fn drop(&mut self) {
+        let mut in_flight = self.limits.in_flight.lock().unwrap();
+        if let Some(count) = in_flight.get_mut(&self.host) {
+            *count -= 1;
+        }
+        self.limits.released.notify_all();
+    }
+}
+
+/// Returns the host of the API at `api_url`.
+fn api_host(api_url: &Url) -> String {
+    api_url.host_str().unwrap_or("").to_owned()
+}
+
+/// A `WikiClient` that sends requests to the wiki over the network.
+///
+/// All requests made through the same client (and its clones) share a pool of keep-alive
+/// connections, so fetching many batches of tasks doesn't open a new connection per request. The
+/// underlying HTTP library only speaks HTTP/1.1, so HTTP/2 is not used.
+///
+/// Clones also share the limits on the number of requests to each host at the same time. See
+/// `HostLimits`.
+///
+/// After `login`, every request carries the session cookies of the wiki.
+///
+/// Requests are built against the API of Rosetta Code. If another wiki is set with
+/// `with_api_url`, requests to the API of Rosetta Code are sent to that wiki instead.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    cookies: BTreeMap<String, String>,
+    user: Option<String>,
+    api_url: Url,
+    wiki_concurrency: usize,
+    host_limits: Arc<HostLimits>,
+}
+
+impl HttpClient {
+    /// Creates a new HTTP client.
+    pub fn new() -> HttpClient {
+        let api_url = Url::parse(API_URL).unwrap();
+        HttpClient {
+            client: reqwest::Client::new(),
+            cookies: BTreeMap::new(),
+            user: None,
+            host_limits: Arc::new(
+                HostLimits::new(DEFAULT_HOST_CONCURRENCY)
+                    .with_limit(&api_host(&api_url), DEFAULT_WIKI_CONCURRENCY),
+            ),
+            api_url,
+            wiki_concurrency: DEFAULT_WIKI_CONCURRENCY,
+        }
+    }
+
+    /// Sets how many requests can be sent to the wiki at the same time. The limit only matters to
+    /// clones of the client that are used on several threads: `TaskIterator` sends its requests
+    /// one at a time.
+    pub fn with_concurrency(mut self, limit: usize) -> HttpClient {
+        self.wiki_concurrency = limit;
+        self.reset_host_limits();
+        self
+    }
+
+    /// Sends the requests to the API of another wiki, such as a private MediaWiki, instead of
+    /// Rosetta Code. `api_url` is the URL of its `api.php`.
+    pub fn with_api_url(mut self, api_url: Url) -> HttpClient {
+        self.api_url = api_url;
+        self.reset_host_limits();
+        self
+    }
+
+    /// Returns the URL of the API of the wiki.
+    pub fn api_url(&self) -> &Url {
+        &self.api_url
+    }
+
+    /// Replaces the limits on the number of requests to each host, after the wiki or its limit
+    /// changed.
+    fn reset_host_limits(&mut self) {
+        let host_limits = HostLimits::new(DEFAULT_HOST_CONCURRENCY)
+            .with_limit(&api_host(&self.api_url), self.wiki_concurrency);
+        self.host_limits = Arc::new(host_limits);
+    }
+
+    /// Returns the URL that a request to `url` is sent to. Requests to the API of Rosetta Code go
+    /// to the configured wiki, and keep their query.
+    fn resolve(&self, url: &Url) -> Url {
+        let default_api = Url::parse(API_URL).unwrap();
+        if url.host_str() != default_api.host_str() || url.path() != default_api.path() {
+            return url.clone();
+        }
+
+        let mut resolved = self.api_url.clone();
+        resolved.set_query(url.query());
+        resolved
+    }
+
+    /// Logs in to the wiki with the MediaWiki login flow, and keeps the session cookies for later
+    /// requests. This is only needed for private wikis.
+    ///
+    /// The password is only sent to the wiki. It is never stored, nor included in any errors.
+    pub fn login(&mut self, user: &str, password: &str) -> Result<()> {
+        let mut token_request = self.api_url.clone();
+        token_request
+            .query_pairs_mut()
+            .append_pair("action", "query")
+            .append_pair("format", "json")
+            .append_pair("meta", "tokens")
+            .append_pair("type", "login");
+
+        let mut response = self.client
+            .get(token_request.as_str())
+            .headers(self.cookie_headers())
+            .send()?;
+        self.store_cookies(response.headers());
+        let tokens: Value = response.json()?;
+        let token = tokens
+            .pointer("/query/tokens/logintoken")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::Parse("the wiki did not return a login token".into()))?
+            .to_owned();
+
+        let params = [
+            ("action", "login"),
+            ("format", "json"),
+            ("lgname", user),
+            ("lgpassword", password),
+            ("lgtoken", token.as_str()),
+        ];
+        let mut response = self.client
+            .post(self.api_url.as_str())
+            .headers(self.cookie_headers())
+            .form(&params)
+            .send()?;
+        self.store_cookies(response.headers());
+        let login: Value = response.json()?;
+
+        match login.pointer("/login/result").and_then(Value::as_str) {
+            Some("Success") => {
+                self.user = Some(user.to_owned());
+                Ok(())
+            }
+            result => {
+                let reason = login
+                    .pointer("/login/reason")
+                    .and_then(Value::as_str)
+                    .or(result)
+                    .unwrap_or("unexpected response");
+                Err(Error::Login {
+                    user: user.to_owned(),
+                    reason: reason.to_owned(),
+                })
+            }
+        }
+    }
+
+    /// Returns the headers that send the session cookies, if there are any.
+    fn cookie_headers(&self) -> Headers {
+        let mut headers = Headers::new();
+        if !self.cookies.is_empty() {
+            headers.set_raw("Cookie", cookie_header(&self.cookies));
+        }
+        headers
+    }
+
+    /// Remembers the cookies set by a response.
+    fn store_cookies(&mut self, headers: &Headers) {
+        if let Some(raw) = headers.get_raw("Set-Cookie") {
+            for line in raw.iter() {
+                if let Some((name, value)) = parse_set_cookie(&String::from_utf8_lossy(line)) {
+                    self.cookies.insert(name, value);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for HttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The values of the cookies identify the session, so they are left out.
+        f.debug_struct("HttpClient")
+            .field("client", &self.client)
+            .field("cookies", &self.cookies.keys().collect::<Vec<_>>())
+            .field("user", &self.user)
+            .field("api_url", &self.api_url)
+            .finish()
+    }
+}
+
+/// Extracts the name and value of a cookie from a `Set-Cookie` header, ignoring its attributes.
+fn parse_set_cookie(header: &str) -> Option<(String, String)> {
+    let pair = header.split(';').next()?;
+    let mut parts = pair.splitn(2, '=');
+    let name = parts.next()?.trim();
+    let value = parts.next()?.trim();
+
+    if name.is_empty() {
+        None
+    } else {
+        Some((name.to_owned(), value.to_owned()))
+    }
+}
+
+/// Formats cookies as the value of a `Cookie` header.
+fn cookie_header(cookies: &BTreeMap<String, String>) -> String {
+    cookies
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        HttpClient::new()
+    }
+}
+
+impl WikiClient for HttpClient {
+    fn get(&self, url: &Url) -> Result<String> {
+        let url = &self.resolve(url);
+        let _permit = self.host_limits.acquire(url);
+        let mut response = self.client
+            .get(url.as_str())
+            .headers(self.cookie_headers())
+            .send()?;
+
+        // Always read the body to the end, even for failed requests. Otherwise, the connection
+        // can't be returned to the pool and reused for the next request.
+        let mut body = String::new();
+        response.read_to_string(&mut body)?;
+
+        if !response.status().is_success() {
+            return Err(Error::Status {
+                url: url.to_string(),
+                status: response.status(),
+            });
+        }
+
+        Ok(body)
+    }
+}
+
+impl ConditionalClient for HttpClient {
+    fn get_if_modified(&self, url: &Url, validators: &Validators) -> Result<ConditionalResponse> {
+        let url = &self.resolve(url);
+        let _permit = self.host_limits.acquire(url);
+        let mut headers = self.cookie_headers();
+        if let Some(ref etag) = validators.etag {
+            headers.set_raw("If-None-Match", etag.clone());
+        }
+        if let Some(ref last_modified) = validators.last_modified {
+            headers.set_raw("If-Modified-Since", last_modified.clone());
+        }
+
+        let mut response = self.client.get(url.as_str()).headers(headers).send()?;
+
+        let mut body = String::new();
+        response.read_to_string(&mut body)?;
+
+        if response.status() == StatusCode::NotModified {
+            return Ok(ConditionalResponse::NotModified);
+        }
+
+        if !response.status().is_success() {
+            return Err(Error::Status {
+                url: url.to_string(),
+                status: response.status(),
+            });
+        }
+
+        let header = |name: &str| {
+            response
+                .headers()
+                .get_raw(name)
+                .and_then(|raw| raw.one())
+                .map(|value| String::from_utf8_lossy(value).into_owned())
+        };
+
+        let validators = Validators {
+            etag: header("ETag"),
+            last_modified: header("Last-Modified"),
+        };
+
+        Ok(ConditionalResponse::Modified { body, validators })
+    }
+
+    /// The key is the URL that the request is sent to, and the user that is logged in, if any.
+    fn cache_key(&self, url: &Url) -> String {
+        let url = self.resolve(url);
+        match self.user {
+            Some(ref user) => format!("{} (as {})", url, user),
+            None => url.to_string(),
+        }
+    }
+}
+
+/// The values that a server returned to identify a version of a response, which can be sent back
+/// to ask whether the response has changed since.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    /// The value of the `ETag` header.
+    pub etag: Option<String>,
+
+    /// The value of the `Last-Modified` header.
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// True if there are no validators, so a response can't be revalidated.
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// The response to a conditional request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionalResponse {
+    /// The response hasn't changed since the version identified by the validators of the
+    /// request.
+    NotModified,
+
+    /// The response has changed, or the request had no validators.
+    Modified {
+        /// The body of the response.
+        body: String,
+
+        /// The validators of the new response.
+        validators: Validators,
+    },
+}
+
+/// A client that can perform conditional GET requests, which only return a body if it changed.
+pub trait ConditionalClient {
+    /// Performs a GET request, including `If-None-Match` and `If-Modified-Since` headers for the
+    /// given validators.
+    fn get_if_modified(&self, url: &Url, validators: &Validators) -> Result<ConditionalResponse>;
+
+    /// Returns the key that identifies the response to `url` in a cache. Clients that send
+    /// requests somewhere else than `url`, or with credentials, must include that in the key, so
+    /// that the responses of different wikis or sessions are kept apart. By default, the key is
+    /// the URL itself.
+    fn cache_key(&self, url: &Url) -> String {
+        url.to_string()
+    }
+}
+
+/// Clients that can't perform conditional requests always download the page again, so their
+/// responses are only cached for the time-to-live of a `CachingClient`.
+impl ConditionalClient for Box<WikiClient> {
+    fn get_if_modified(&self, url: &Url, _: &Validators) -> Result<ConditionalResponse> {
+        Ok(ConditionalResponse::Modified {
+            body: self.get(url)?,
+            validators: Validators::default(),
+        })
+    }
+}
+
+/// Counts how the responses of a `CachingClient` were obtained.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// The number of responses that were served from the cache without any request.
+    pub hits: usize,
+
+    /// The number of responses that were downloaded, because they weren't cached or had changed.
+    pub misses: usize,
+
+    /// The number of cached responses that the wiki confirmed to be unchanged.
+    pub revalidated: usize,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cache: {} hits, {} misses, {} revalidated",
+            self.hits, self.misses, self.revalidated
+        )
+    }
+}
+
+/// A response stored in the cache of a `CachingClient`.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// The key of the request, as returned by `ConditionalClient::cache_key`.
+    key: String,
+    validators: Validators,
+    body: String,
+
+    /// When the response was last fetched or revalidated, in seconds since the Unix epoch.
+    fetched_at: u64,
+}
+
+/// A `WikiClient` that stores responses on disk, so that unchanged pages aren't downloaded again
+/// by later runs.
+///
+/// If a cached response has an `ETag` or `Last-Modified` validator, it is revalidated with a
+/// conditional request, and reused if the server answers `304 Not Modified`. The MediaWiki API
+/// doesn't send validators for most queries, so responses without them are reused without any
+/// request until they are older than the time-to-live of the cache.
+///
+/// Requests for the IDs of the latest revisions of pages are never cached, so that a
+/// `RevisionCache` always compares its pages against the current revisions.
+#[derive(Debug)]
+pub struct CachingClient<C> {
+    client: C,
+    cache_dir: PathBuf,
+    ttl: Duration,
+    stats: Cell<CacheStats>,
+}
+
+impl<C> CachingClient<C>
+where
+    C: ConditionalClient,
+{
+    /// Creates a client that performs requests with `client`, and stores responses in
+    /// `cache_dir`. Responses without validators are considered fresh for `ttl`.
+    pub fn new<P: Into<PathBuf>>(client: C, cache_dir: P, ttl: Duration) -> CachingClient<C> {
+        CachingClient {
+            client,
+            cache_dir: cache_dir.into(),
+            ttl,
+            stats: Cell::new(CacheStats::default()),
+        }
+    }
+
+    /// Returns how the responses of the client were obtained so far.
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+
+    /// Updates the counts of the statistics.
+    fn record<F: FnOnce(&mut CacheStats)>(&self, update: F) {
+        let mut stats = self.stats.get();
+        update(&mut stats);
+        self.stats.set(stats);
+    }
+
+    /// Returns the path of the cache file for the key of a request.
+    fn entry_path(&self, key: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        self.cache_dir
+            .join(format!("{:016x}.json", hasher.finish()))
+    }
+
+    /// Reads the cached response for a URL, if there is one.
+    fn read_entry(&self, url: &Url) -> Option<CacheEntry> {
+        let key = self.client.cache_key(url);
+        let contents = fs::read_to_string(self.entry_path(&key)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&contents).ok()?;
+
+        // Guard against hash collisions.
+        if entry.key == key {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    /// Stores a response in the cache. Failing to write the cache only logs a warning, since
+    /// the response can still be used.
+    fn write_entry(&self, entry: &CacheEntry) {
+        let result = fs::create_dir_all(&self.cache_dir)
+            .map_err(Error::from)
+            .and_then(|_| Ok(serde_json::to_string(entry)?))
+            .and_then(|json| Ok(fs::write(self.entry_path(&entry.key), json)?));
+
+        if let Err(err) = result {
+            warn!("could not cache the response of {}: {}", entry.key, err);
+        }
+    }
+}
+
+/// True if the request only asks for the IDs of the latest revisions of some pages. These tell if
+/// the pages in a `RevisionCache` are still current, and the wiki sends no validators for them,
+/// so a `CachingClient` never caches them.
+fn is_revision_probe(url: &Url) -> bool {
+    url.query_pairs()
+        .any(|(key, value)| key == "rvprop" && value == "ids")
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+impl<C> WikiClient for CachingClient<C>
+where
+    C: ConditionalClient,
+{
+    fn get(&self, url: &Url) -> Result<String> {
+        if is_revision_probe(url) {
+            debug!("not cached: {}", url);
+            return match self.client.get_if_modified(url, &Validators::default())? {
+                ConditionalResponse::Modified { body, .. } => Ok(body),
+                ConditionalResponse::NotModified => Err(Error::NotFound(format!(
+                    "{} was reported as not modified, but no validators were sent",
+                    url
+                ))),
+            };
+        }
+
+        let cached = self.read_entry(url);
+
+        if let Some(ref entry) = cached {
+            let age = now().saturating_sub(entry.fetched_at);
+            if entry.validators.is_empty() && age < self.ttl.as_secs() {
+                debug!("cache hit: {}", url);
+                self.record(|stats| stats.hits += 1);
+                return Ok(entry.body.clone());
+            }
+        }
+
+        let validators = cached
+            .as_ref()
+            .map(|entry| entry.validators.clone())
+            .unwrap_or_default();
+
+        let entry = match self.client.get_if_modified(url, &validators)? {
+            ConditionalResponse::NotModified => match cached {
+                Some(entry) => {
+                    debug!("cache revalidated: {}", url);
+                    self.record(|stats| stats.revalidated += 1);
+                    CacheEntry {
+                        fetched_at: now(),
+                        ..entry
+                    }
+                }
+                None => {
+                    return Err(Error::NotFound(format!(
+                        "{} was reported as not modified, but is not cached",
+                        url
+                    )))
+                }
+            },
+            ConditionalResponse::Modified { body, validators } => {
+                debug!("cache miss: {}", url);
+                self.record(|stats| stats.misses += 1);
+                CacheEntry {
+                    key: self.client.cache_key(url),
+                    validators,
+                    body,
+                    fetched_at: now(),
+                }
+            }
+        };
+
+        self.write_entry(&entry);
+        Ok(entry.body)
+    }
+}
+
+/// A page stored in a `RevisionCache`.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedRevision {
+    title: String,
+    revid: u64,
+    content: String,
+}
+
+/// Stores the wiki markup of pages on disk along with the ID of their revision, so that a page is
+/// only downloaded again once it has a newer revision.
+///
+/// Unlike the responses of a `CachingClient`, a cached page is never reused after it changed,
+/// because its revision is compared against the latest revision of the page, which is cheap to
+/// request. See `TaskIndexBuilder::revision_cache`.
+///
+/// Pages are stored by title, so each wiki, and each user that logs in to it, needs a directory of
+/// its own.
+#[derive(Debug)]
+pub struct RevisionCache {
+    cache_dir: PathBuf,
+    stats: Cell<CacheStats>,
+}
+
+impl RevisionCache {
+    /// Creates a cache that stores pages in `cache_dir`.
+    pub fn new<P: Into<PathBuf>>(cache_dir: P) -> RevisionCache {
+        RevisionCache {
+            cache_dir: cache_dir.into(),
+            stats: Cell::new(CacheStats::default()),
+        }
+    }
+
+    /// Returns how many pages were reused and downloaded so far. Reused pages are counted as
+    /// revalidated, since their revision was checked first.
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+
+    /// Returns the wiki markup of a page if it is cached at the given revision.
+    pub fn get(&self, title: &str, revid: u64) -> Option<String> {
+        let contents = fs::read_to_string(self.entry_path(title)).ok()?;
+        let entry: CachedRevision = serde_json::from_str(&contents).ok()?;
+
+        // Guard against hash collisions, and against pages that changed since they were cached.
+        if entry.title != title || entry.revid != revid {
+            return None;
+        }
+
+        debug!("revision cache hit: {} at {}", title, revid);
+        self.record(|stats| stats.revalidated += 1);
+
+        Some(entry.content)
+    }
+
+    /// Stores the wiki markup of a page at a revision, replacing older revisions of the page.
+    /// Failing to write the cache only logs a warning, since the page can still be used.
+    pub fn insert(&self, title: &str, revid: u64, content: &str) {
+        self.record(|stats| stats.misses += 1);
+
+        let entry = CachedRevision {
+            title: title.to_owned(),
+            revid,
+            content: content.to_owned(),
+        };
+        let result = fs::create_dir_all(&self.cache_dir)
+            .map_err(Error::from)
+            .and_then(|_| Ok(serde_json::to_string(&entry)?))
+            .and_then(|json| Ok(fs::write(self.entry_path(title), json)?));
+
+        if let Err(err) = result {
+            warn!("could not cache the page '{}': {}", title, err);
+        }
+    }
+
+    /// Updates the counts of the statistics.
+    fn record<F: FnOnce(&mut CacheStats)>(&self, update: F) {
+        let mut stats = self.stats.get();
+        update(&mut stats);
+        self.stats.set(stats);
+    }
+
+    /// Returns the path of the cache file for a page.
+    fn entry_path(&self, title: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        title.hash(&mut hasher);
+        self.cache_dir
+            .join(format!("{:016x}.json", hasher.finish()))
+    }
+}
+
+/// A `WikiClient` that serves canned responses instead of performing real requests.
+///
+/// Each response is associated with a set of query parameters. A request is answered with the
+/// response whose parameters are all present in the request URL. If several responses match, the
+/// one with the most parameters wins, so more specific responses can override general ones.
+#[derive(Debug, Default)]
+pub struct MockWikiClient {
+    responses: Vec<(Vec<(String, String)>, String)>,
+    requests: RefCell<Vec<Url>>,
+}
+
+impl MockWikiClient {
+    /// Creates a client without any canned responses.
+    pub fn new() -> MockWikiClient {
+        Default::default()
+    }
+
+    /// Adds a response that will be returned for requests containing all of the given query
+    /// parameters.
+    pub fn with_response(mut self, params: &[(&str, &str)], body: &str) -> MockWikiClient {
+        let params = params
+            .iter()
+            .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+            .collect();
+        self.responses.push((params, body.to_owned()));
+        self
+    }
+
+    /// Returns every URL that has been requested from this client, in order.
+    pub fn requests(&self) -> Vec<Url> {
+        self.requests.borrow().clone()
+    }
+}
+
+impl WikiClient for MockWikiClient {
+    fn get(&self, url: &Url) -> Result<String> {
+        self.requests.borrow_mut().push(url.clone());
+
+        let query = url.query_pairs().into_owned().collect::<Vec<_>>();
+
+        self.responses
+            .iter()
+            .filter(|&&(ref params, _)| params.iter().all(|param| query.contains(param)))
+            .max_by_key(|&&(ref params, _)| params.len())
+            .map(|&(_, ref body)| body.clone())
+            .ok_or_else(|| Error::NotFound(format!("no canned response for {}", url)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use reqwest::Url;
+
+    use super::{
+        cookie_header, parse_set_cookie, CachingClient, ConditionalClient, ConditionalResponse,
+        HostLimits, HttpClient, MockWikiClient, Validators, WikiClient,
+    };
+    use temp_dir::TempDir;
+    use Result;
+
+    /// A server that always returns the same page with an `ETag`, and answers `304 Not Modified`
+    /// whenever it receives the `ETag` back.
+    #[derive(Default)]
+    struct EtagServer {
+        requests: RefCell<Vec<Validators>>,
+    }
+
+    impl ConditionalClient for EtagServer {
+        fn get_if_modified(&self, _: &Url, validators: &Validators) -> Result<ConditionalResponse> {
+            self.requests.borrow_mut().push(validators.clone());
+
+            if validators.etag == Some(String::from("\"v1\"")) {
+                Ok(ConditionalResponse::NotModified)
+            } else {
+                Ok(ConditionalResponse::Modified {
+                    body: String::from("page"),
+                    validators: Validators {
+                        etag: Some(String::from("\"v1\"")),
+                        last_modified: None,
+                    },
+                })
+            }
+        }
+    }
+
+    #[test]
+    fn cached_response_not_modified() {
+        let cache_dir = TempDir::new("rosetta-meta-http-cache-not-modified");
+        let client = CachingClient::new(
+            EtagServer::default(),
+            cache_dir.path(),
+            Duration::from_secs(0),
+        );
+        let url = Url::parse("http://rosettacode.org/mw/api.php?action=query").unwrap();
+
+        assert_eq!(client.get(&url).unwrap(), "page");
+        assert_eq!(client.get(&url).unwrap(), "page");
+
+        let requests = client.client.requests.borrow();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].is_empty());
+        assert_eq!(requests[1].etag, Some(String::from("\"v1\"")));
+
+        let stats = client.stats();
+        assert_eq!((stats.hits, stats.misses, stats.revalidated), (0, 1, 1));
+        assert_eq!(stats.to_string(), "cache: 0 hits, 1 misses, 1 revalidated");
+    }
+
+    #[test]
+    fn host_limits() {
+        let limits = Arc::new(HostLimits::new(2).with_limit("rosettacode.org", 1));
+        assert_eq!(limits.limit("rosettacode.org"), 1);
+        assert_eq!(limits.limit("gist.githubusercontent.com"), 2);
+
+        let wiki = Url::parse("http://rosettacode.org/mw/api.php").unwrap();
+        let permit = limits.acquire(&wiki);
+
+        // Other hosts have their own limit.
+        let gist = Url::parse("https://gist.githubusercontent.com/a/b/raw").unwrap();
+        drop(limits.acquire(&gist));
+
+        let acquired = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let limits = limits.clone();
+            let acquired = acquired.clone();
+            thread::spawn(move || {
+                let _permit = limits.acquire(&wiki);
+                acquired.store(true, Ordering::SeqCst);
+            })
+        };
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(!acquired.load(Ordering::SeqCst));
+
+        drop(permit);
+        waiter.join().unwrap();
+        assert!(acquired.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn session_cookies() {
+        assert_eq!(
+            parse_set_cookie("wiki_session=abc123; path=/; secure; HttpOnly"),
+            Some((String::from("wiki_session"), String::from("abc123")))
+        );
+        assert_eq!(parse_set_cookie("=abc123"), None);
+        assert_eq!(parse_set_cookie("HttpOnly"), None);
+
+        let mut cookies = BTreeMap::new();
+        cookies.insert(String::from("wikiUserName"), String::from("Rosetta"));
+        cookies.insert(String::from("wiki_session"), String::from("abc123"));
+        assert_eq!(
+            cookie_header(&cookies),
+            "wikiUserName=Rosetta; wiki_session=abc123"
+        );
+    }
+
+    #[test]
+    fn custom_api_url() {
+        let api_url = Url::parse("https://wiki.example.com/w/api.php").unwrap();
+        let client = HttpClient::new().with_api_url(api_url.clone());
+        assert_eq!(client.api_url(), &api_url);
+        assert_eq!(client.host_limits.limit("wiki.example.com"), 2);
+
+        let request = Url::parse("http://rosettacode.org/mw/api.php?action=query").unwrap();
+        assert_eq!(
+            client.resolve(&request).as_str(),
+            "https://wiki.example.com/w/api.php?action=query"
+        );
+
+        let other = Url::parse("https://crates.io/api/v1/crates").unwrap();
+        assert_eq!(client.resolve(&other), other);
+
+        // Responses of the other wiki are cached apart from those of Rosetta Code.
+        assert_eq!(
+            client.cache_key(&request),
+            "https://wiki.example.com/w/api.php?action=query"
+        );
+        assert_ne!(
+            client.cache_key(&request),
+            HttpClient::new().cache_key(&request)
+        );
+    }
+
+    #[test]
+    fn most_specific_response() {
+        let client = MockWikiClient::new()
+            .with_response(&[("action", "query")], "general")
+            .with_response(
+                &[("action", "query"), ("list", "categorymembers")],
+                "specific",
+            );
+
+        let general = Url::parse("http://rosettacode.org/mw/api.php?action=query").unwrap();
+        let specific =
+            Url::parse("http://rosettacode.org/mw/api.php?list=categorymembers&action=query")
+                .unwrap();
+
+        assert_eq!(client.get(&general).unwrap(), "general");
+        assert_eq!(client.get(&specific).unwrap(), "specific");
+        assert_eq!(client.requests(), vec![general, specific]);
+    }
+
+    #[test]
+    fn no_matching_response() {
+        let client = MockWikiClient::new().with_response(&[("action", "parse")], "");
+        let url = Url::parse("http://rosettacode.org/mw/api.php?action=query").unwrap();
+
+        assert!(client.get(&url).is_err());
+    }
+}
@@ -0,0 +1,742 @@
+//! Utilities for comparing the local and remote implementations of a task.
+
+use std::cmp;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::usize;
+
+/// The number of lines that either side of a diff needs to have more than for the streaming diff
+/// to be used, unless `set_streaming_threshold` changes it.
+pub const DEFAULT_STREAMING_THRESHOLD: usize = 5000;
+
+lazy_static! {
+    static ref STREAMING_THRESHOLD: AtomicUsize = AtomicUsize::new(DEFAULT_STREAMING_THRESHOLD);
+}
+
+/// Makes all diffs use the streaming diff of `streaming_changeset` if either side has more than
+/// `lines` lines, or never if `lines` is `None`.
+///
+/// The threshold applies to `changeset`, `unified_diff`, `changed_lines` and `similarity`, and so
+/// to the diffs of a `Task` as well. Set it before any diffs are computed: tasks cache their
+/// changesets.
+pub fn set_streaming_threshold(lines: Option<usize>) {
+    STREAMING_THRESHOLD.store(lines.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// Describes how the local implementation of a task differs from the implementation on the wiki.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    /// The local and remote code are exactly the same.
+    Identical,
+
+    /// The code only differs by trailing whitespace or trailing newlines.
+    WhitespaceOnly,
+
+    /// The code only differs by indentation, such as tabs instead of spaces or a different
+    /// indentation width. Differences in trailing whitespace may be present as well.
+    IndentationOnly,
+
+    /// The code differs in a way that is worth looking at.
+    Substantive,
+
+    /// The task is missing either a local or a remote implementation, so there is nothing to
+    /// compare.
+    Missing,
+}
+
+/// Classifies the difference between the local and remote code of a task.
+pub fn diff_kind(local: Option<&str>, remote: Option<&str>) -> DiffKind {
+    match (local, remote) {
+        (Some(local), Some(remote)) => {
+            if local == remote {
+                DiffKind::Identical
+            } else if trim_trailing_whitespace(local) == trim_trailing_whitespace(remote) {
+                DiffKind::WhitespaceOnly
+            } else if is_indentation_only(local, remote) {
+                DiffKind::IndentationOnly
+            } else {
+                DiffKind::Substantive
+            }
+        }
+        _ => DiffKind::Missing,
+    }
+}
+
+/// True if the lines of both texts are the same apart from the whitespace around them. Trailing
+/// blank lines are ignored.
+fn is_indentation_only(a: &str, b: &str) -> bool {
+    let lines = |code: &str| {
+        let mut lines = code.lines().map(str::trim).collect::<Vec<_>>();
+        while lines.last() == Some(&"") {
+            lines.pop();
+        }
+        lines
+    };
+
+    lines(a) == lines(b)
+}
+
+/// Strips trailing whitespace from every line, as well as any trailing newlines.
+fn trim_trailing_whitespace(code: &str) -> String {
+    code.lines()
+        .map(|line| line.trim_right())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_right()
+        .to_owned()
+}
+
+/// Generates a unified diff that turns `old` into `new`, with `context` unchanged lines around
+/// each change.
+///
+/// The labels are used for the `---` and `+++` header lines. The diff is line-based, so a missing
+/// trailing newline is not reported. Returns an empty string if the texts have the same lines.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+) -> String {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+    let edits = edit_script(&old_lines, &new_lines);
+
+    // Ranges of edits that make up each hunk. Changes that are close enough to share context are
+    // merged into the same hunk.
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for (index, edit) in edits.iter().enumerate() {
+        if let Edit::Same(_) = *edit {
+            continue;
+        }
+
+        let start = index.saturating_sub(context);
+        let end = cmp::min(edits.len(), index + context + 1);
+
+        let merge = hunks
+            .last()
+            .map_or(false, |&(_, hunk_end)| start <= hunk_end);
+        if merge {
+            hunks.last_mut().unwrap().1 = end;
+        } else {
+            hunks.push((start, end));
+        }
+    }
+
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = format!("--- {}\n+++ {}\n", old_label, new_label);
+
+    for (start, end) in hunks {
+        let (old_before, new_before) = line_counts(&edits[..start]);
+        let (old_len, new_len) = line_counts(&edits[start..end]);
+
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            range_start(old_before, old_len),
+            old_len,
+            range_start(new_before, new_len),
+            new_len
+        ));
+
+        for edit in &edits[start..end] {
+            let (prefix, line) = match *edit {
+                Edit::Same(line) => (' ', line),
+                Edit::Remove(line) => ('-', line),
+                Edit::Add(line) => ('+', line),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+
+    diff
+}
+
+/// A run of consecutive lines that a diff keeps, removes or adds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    /// Lines that are in both texts.
+    Same(Vec<String>),
+
+    /// Lines that are only in the old text.
+    Remove(Vec<String>),
+
+    /// Lines that are only in the new text.
+    Add(Vec<String>),
+}
+
+impl Change {
+    /// Returns the lines of the change.
+    pub fn lines(&self) -> &[String] {
+        match *self {
+            Change::Same(ref lines) | Change::Remove(ref lines) | Change::Add(ref lines) => lines,
+        }
+    }
+}
+
+/// Computes the changes that turn `old` into `new`, line by line.
+///
+/// Consecutive lines that are kept, removed or added are grouped into a single `Change`, so the
+/// changes alternate between kinds, except that a removal may be directly followed by an addition.
+/// The changes are minimal, unless either text has more lines than the streaming threshold of
+/// `set_streaming_threshold`.
+pub fn changeset(old: &str, new: &str) -> Vec<Change> {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+
+    group_edits(edit_script(&old_lines, &new_lines))
+}
+
+/// The number of lines that `streaming_changeset` looks ahead to find where the texts match again.
+const LOOKAHEAD: usize = 200;
+
+/// Computes the changes that turn `old` into `new`, like `changeset`, but without the quadratic
+/// memory of finding a minimal diff.
+///
+/// Lines are compared in a single pass. When they differ, the next `LOOKAHEAD` lines of each text
+/// are searched for the nearest point where the texts match again. This only needs memory
+/// proportional to the size of the texts, but the changes are not always minimal: edits that are
+/// far apart from their matching lines, such as moved blocks, show up as larger removals and
+/// additions.
+pub fn streaming_changeset(old: &str, new: &str) -> Vec<Change> {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+
+    group_edits(streaming_edit_script(&old_lines, &new_lines))
+}
+
+/// Computes the edit script of `streaming_changeset`.
+fn streaming_edit_script<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Edit<'a>> {
+    let mut edits = vec![];
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            edits.push(Edit::Same(old[i]));
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        // The number of lines to skip in the new text to find the current old line, and vice
+        // versa.
+        let added = new[j..]
+            .iter()
+            .take(LOOKAHEAD)
+            .position(|line| *line == old[i]);
+        let removed = old[i..]
+            .iter()
+            .take(LOOKAHEAD)
+            .position(|line| *line == new[j]);
+
+        // Prefer the shorter detour, and additions if both are equally long.
+        let prefer_added = match (added, removed) {
+            (Some(added), Some(removed)) => added <= removed,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if prefer_added {
+            let added = added.unwrap();
+            edits.extend(new[j..j + added].iter().map(|line| Edit::Add(line)));
+            j += added;
+        } else if let Some(removed) = removed {
+            let skipped = &old[i..i + removed];
+            edits.extend(skipped.iter().map(|line| Edit::Remove(line)));
+            i += removed;
+        } else {
+            edits.push(Edit::Remove(old[i]));
+            edits.push(Edit::Add(new[j]));
+            i += 1;
+            j += 1;
+        }
+    }
+    edits.extend(old[i..].iter().map(|line| Edit::Remove(line)));
+    edits.extend(new[j..].iter().map(|line| Edit::Add(line)));
+
+    edits
+}
+
+/// Groups consecutive edits of the same kind into changes.
+fn group_edits(edits: Vec<Edit>) -> Vec<Change> {
+    let mut changes: Vec<Change> = vec![];
+
+    for edit in edits {
+        let line = match edit {
+            Edit::Same(line) | Edit::Remove(line) | Edit::Add(line) => line.to_owned(),
+        };
+
+        let extends_last = match (changes.last(), edit) {
+            (Some(&Change::Same(_)), Edit::Same(_))
+            | (Some(&Change::Remove(_)), Edit::Remove(_))
+            | (Some(&Change::Add(_)), Edit::Add(_)) => true,
+            _ => false,
+        };
+
+        if extends_last {
+            match *changes.last_mut().unwrap() {
+                Change::Same(ref mut lines)
+                | Change::Remove(ref mut lines)
+                | Change::Add(ref mut lines) => lines.push(line),
+            }
+        } else {
+            changes.push(match edit {
+                Edit::Same(_) => Change::Same(vec![line]),
+                Edit::Remove(_) => Change::Remove(vec![line]),
+                Edit::Add(_) => Change::Add(vec![line]),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Counts the lines that a changeset adds and removes, in that order.
+pub fn line_changes(changes: &[Change]) -> (usize, usize) {
+    let mut added = 0;
+    let mut removed = 0;
+    for change in changes {
+        match *change {
+            Change::Add(ref lines) => added += lines.len(),
+            Change::Remove(ref lines) => removed += lines.len(),
+            Change::Same(_) => (),
+        }
+    }
+
+    (added, removed)
+}
+
+/// Counts the lines that have to be removed or added to turn `old` into `new`.
+pub fn changed_lines(old: &str, new: &str) -> usize {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+
+    edit_script(&old_lines, &new_lines)
+        .into_iter()
+        .filter(|edit| match *edit {
+            Edit::Same(_) => false,
+            Edit::Remove(_) | Edit::Add(_) => true,
+        })
+        .count()
+}
+
+/// Returns how similar two texts are, from `0.0` if they have no lines in common to `1.0` if they
+/// have the same lines.
+///
+/// The ratio is the number of lines that are kept by the diff, divided by the average number of
+/// lines of both texts, so it doesn't depend on which text is the old one. Two empty texts are
+/// identical.
+pub fn similarity(old: &str, new: &str) -> f64 {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+
+    let total = old_lines.len() + new_lines.len();
+    if total == 0 {
+        return 1.0;
+    }
+
+    let matched = edit_script(&old_lines, &new_lines)
+        .into_iter()
+        .filter(|edit| match *edit {
+            Edit::Same(_) => true,
+            Edit::Remove(_) | Edit::Add(_) => false,
+        })
+        .count();
+
+    2.0 * matched as f64 / total as f64
+}
+
+/// Counts the lines of the old and new text that are covered by some edits.
+fn line_counts(edits: &[Edit]) -> (usize, usize) {
+    (
+        edits.iter().filter(|edit| edit.in_old()).count(),
+        edits.iter().filter(|edit| edit.in_new()).count(),
+    )
+}
+
+/// Returns the line number that a hunk header uses for a range, given the number of lines before
+/// it. Line numbers are one-based, except that an empty range refers to the line before it.
+fn range_start(lines_before: usize, len: usize) -> usize {
+    if len == 0 {
+        lines_before
+    } else {
+        lines_before + 1
+    }
+}
+
+/// A single step of a line-based edit script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit<'a> {
+    Same(&'a str),
+    Remove(&'a str),
+    Add(&'a str),
+}
+
+impl<'a> Edit<'a> {
+    /// True if the line is part of the old text.
+    fn in_old(&self) -> bool {
+        match *self {
+            Edit::Same(_) | Edit::Remove(_) => true,
+            Edit::Add(_) => false,
+        }
+    }
+
+    /// True if the line is part of the new text.
+    fn in_new(&self) -> bool {
+        match *self {
+            Edit::Same(_) | Edit::Add(_) => true,
+            Edit::Remove(_) => false,
+        }
+    }
+}
+
+/// Computes an edit script between two lists of lines. The edit script is minimal, unless either
+/// list is longer than the streaming threshold.
+fn edit_script<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Edit<'a>> {
+    edit_script_with_threshold(old, new, STREAMING_THRESHOLD.load(Ordering::Relaxed))
+}
+
+/// Computes an edit script between two lists of lines, using the streaming diff if either list
+/// has more than `threshold` lines.
+fn edit_script_with_threshold<'a>(
+    old: &[&'a str],
+    new: &[&'a str],
+    threshold: usize,
+) -> Vec<Edit<'a>> {
+    if old.len() > threshold || new.len() > threshold {
+        streaming_edit_script(old, new)
+    } else {
+        minimal_edit_script(old, new)
+    }
+}
+
+/// Computes a minimal edit script between two lists of lines using their longest common
+/// subsequence.
+fn minimal_edit_script<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Edit<'a>> {
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                cmp::max(lcs[i + 1][j], lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            edits.push(Edit::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            edits.push(Edit::Remove(old[i]));
+            i += 1;
+        } else {
+            edits.push(Edit::Add(new[j]));
+            j += 1;
+        }
+    }
+    edits.extend(old[i..].iter().map(|line| Edit::Remove(line)));
+    edits.extend(new[j..].iter().map(|line| Edit::Add(line)));
+
+    edits
+}
+
+/// Describes which kind of Rust construct was changed by a hunk of a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HunkKind {
+    /// The hunk changes a function signature.
+    Signature,
+
+    /// The hunk only changes imports, i.e., `use` declarations or `extern crate` items.
+    Imports,
+
+    /// The hunk changes anything else, such as the bodies of functions.
+    Body,
+}
+
+impl fmt::Display for HunkKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            HunkKind::Signature => "signature",
+            HunkKind::Imports => "imports",
+            HunkKind::Body => "body",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Classifies a hunk of a diff, given its added and removed lines.
+///
+/// This is a simple heuristic based on how each line starts, not a parser. A hunk that touches
+/// any function signature is a `Signature` change, even if it changes imports as well. A hunk
+/// whose non-blank lines are all imports is an `Imports` change. Everything else is `Body`.
+pub fn classify_hunk<'a, I>(lines: I) -> HunkKind
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut imports = false;
+    let mut other = false;
+
+    for line in lines {
+        let line = strip_visibility(line.trim());
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if is_signature(line) {
+            return HunkKind::Signature;
+        }
+
+        if line.starts_with("use ") || line.starts_with("extern crate ") {
+            imports = true;
+        } else {
+            other = true;
+        }
+    }
+
+    if imports && !other {
+        HunkKind::Imports
+    } else {
+        HunkKind::Body
+    }
+}
+
+/// Removes a leading `pub` or `pub(...)` visibility from a line.
+fn strip_visibility(line: &str) -> &str {
+    if line.starts_with("pub(") {
+        if let Some(end) = line.find(')') {
+            return line[end + 1..].trim_left();
+        }
+    }
+
+    if line.starts_with("pub ") {
+        return line[4..].trim_left();
+    }
+
+    line
+}
+
+/// True if a line (without visibility) looks like the start of a function declaration.
+fn is_signature(line: &str) -> bool {
+    let line = if line.starts_with("unsafe ") {
+        line[7..].trim_left()
+    } else {
+        line
+    };
+
+    line.starts_with("fn ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        changed_lines, changeset, classify_hunk, diff_kind, edit_script_with_threshold,
+        group_edits, similarity, streaming_changeset, unified_diff, Change, DiffKind, HunkKind,
+    };
+
+    #[test]
+    fn identical() {
+        assert_eq!(
+            diff_kind(Some("fn main() {}\n"), Some("fn main() {}\n")),
+            DiffKind::Identical
+        );
+    }
+
+    #[test]
+    fn trailing_newline() {
+        assert_eq!(
+            diff_kind(Some("fn main() {}\n"), Some("fn main() {}")),
+            DiffKind::WhitespaceOnly
+        );
+        assert_eq!(
+            diff_kind(Some("fn main() {  \n}\n\n"), Some("fn main() {\n}\n")),
+            DiffKind::WhitespaceOnly
+        );
+    }
+
+    #[test]
+    fn indentation_only() {
+        assert_eq!(
+            diff_kind(
+                Some("fn main() {\n\tprintln!();\n}\n"),
+                Some("fn main() {\n    println!();\n}\n")
+            ),
+            DiffKind::IndentationOnly
+        );
+        assert_eq!(
+            diff_kind(
+                Some("fn main() {\n  println!();  \n}\n\n"),
+                Some("fn main() {\n    println!();\n}")
+            ),
+            DiffKind::IndentationOnly
+        );
+        assert_eq!(
+            diff_kind(
+                Some("fn main() {\n\tprintln!();\n}\n"),
+                Some("fn main() {\n    println!( );\n}\n")
+            ),
+            DiffKind::Substantive
+        );
+    }
+
+    #[test]
+    fn substantive() {
+        assert_eq!(
+            diff_kind(Some("fn main() {}"), Some("fn main() { }")),
+            DiffKind::Substantive
+        );
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(diff_kind(None, Some("fn main() {}")), DiffKind::Missing);
+        assert_eq!(diff_kind(Some("fn main() {}"), None), DiffKind::Missing);
+        assert_eq!(diff_kind(None, None), DiffKind::Missing);
+    }
+
+    #[test]
+    fn classify_imports() {
+        assert_eq!(
+            classify_hunk(vec!["use std::io;", "", "extern crate rand;"]),
+            HunkKind::Imports
+        );
+        assert_eq!(classify_hunk(vec!["pub use std::fmt;"]), HunkKind::Imports);
+    }
+
+    #[test]
+    fn classify_signature() {
+        assert_eq!(
+            classify_hunk(vec!["use std::io;", "fn main() {"]),
+            HunkKind::Signature
+        );
+        assert_eq!(
+            classify_hunk(vec!["    pub(crate) unsafe fn foo(x: u32) -> u32 {"]),
+            HunkKind::Signature
+        );
+    }
+
+    #[test]
+    fn classify_body() {
+        assert_eq!(
+            classify_hunk(vec!["    let x = 5;", "use std::io;"]),
+            HunkKind::Body
+        );
+        assert_eq!(classify_hunk(vec!["    // fn main() {"]), HunkKind::Body);
+    }
+
+    #[test]
+    fn unified_diff_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\n";
+
+        assert_eq!(
+            unified_diff(old, new, "old", "new", 1),
+            "--- old\n+++ new\n\
+             @@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n\
+             @@ -9,1 +9,2 @@\n i\n+j\n"
+        );
+
+        // With more context, both changes share a single hunk.
+        assert_eq!(
+            unified_diff(old, new, "old", "new", 4)
+                .lines()
+                .filter(|line| line.starts_with("@@"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn count_changed_lines() {
+        assert_eq!(changed_lines("a\nb\nc\n", "a\nB\nc\nd\n"), 3);
+        assert_eq!(changed_lines("a\n", "a"), 0);
+    }
+
+    #[test]
+    fn grouped_changeset() {
+        let lines = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect();
+
+        assert_eq!(
+            changeset("a\nb\nc\nd\n", "a\nB\nC\nd\ne\n"),
+            vec![
+                Change::Same(lines(&["a"])),
+                Change::Remove(lines(&["b", "c"])),
+                Change::Add(lines(&["B", "C"])),
+                Change::Same(lines(&["d"])),
+                Change::Add(lines(&["e"])),
+            ]
+        );
+        assert_eq!(changeset("", ""), vec![]);
+    }
+
+    #[test]
+    fn streaming() {
+        let lines = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect();
+
+        assert_eq!(
+            streaming_changeset("a\nb\nc\nd\n", "a\nx\nc\nd\ne\n"),
+            vec![
+                Change::Same(lines(&["a"])),
+                Change::Remove(lines(&["b"])),
+                Change::Add(lines(&["x"])),
+                Change::Same(lines(&["c", "d"])),
+                Change::Add(lines(&["e"])),
+            ]
+        );
+        assert_eq!(
+            streaming_changeset("a\nd\n", "a\nb\nc\nd\n"),
+            changeset("a\nd\n", "a\nb\nc\nd\n")
+        );
+        assert_eq!(
+            streaming_changeset("a\nb\nc\nd\n", "a\nd\n"),
+            changeset("a\nb\nc\nd\n", "a\nd\n")
+        );
+    }
+
+    #[test]
+    fn streaming_threshold() {
+        let old = ["a", "b"];
+        let new = ["b", "b", "a", "b"];
+
+        let minimal = group_edits(edit_script_with_threshold(&old, &new, 4));
+        let streaming = group_edits(edit_script_with_threshold(&old, &new, 3));
+        assert_eq!(minimal, changeset("a\nb\n", "b\nb\na\nb\n"));
+        assert_eq!(streaming, streaming_changeset("a\nb\n", "b\nb\na\nb\n"));
+        assert_ne!(minimal, streaming);
+    }
+
+    #[test]
+    fn similarity_ratio() {
+        assert_eq!(similarity("a\nb\n", "a\nb\n"), 1.0);
+        assert_eq!(similarity("a\nb\n", "c\nd\n"), 0.0);
+        assert_eq!(similarity("a\nb\nc\n", "a\nc\n"), 0.8);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("", "a\n"), 0.0);
+    }
+
+    #[test]
+    fn unified_diff_identical() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb", "old", "new", 3), "");
+    }
+
+    #[test]
+    fn unified_diff_empty_side() {
+        assert_eq!(
+            unified_diff("", "a\n", "old", "new", 3),
+            "--- old\n+++ new\n@@ -0,0 +1,1 @@\n+a\n"
+        );
+    }
+}
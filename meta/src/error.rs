@@ -0,0 +1,247 @@
+//! Error types for the crate.
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use reqwest::{self, StatusCode, UrlError};
+use serde_json;
+use toml;
+
+/// The errors returned by the crate.
+#[derive(Debug)]
+pub enum Error {
+    /// A file could not be read or written.
+    Io(io::Error),
+
+    /// A request could not be sent, or its response could not be received.
+    Http(reqwest::Error),
+
+    /// The server answered a request with an error status.
+    Status {
+        /// The URL that was requested.
+        url: String,
+
+        /// The status of the response.
+        status: StatusCode,
+    },
+
+    /// A response or cache file is not the expected JSON.
+    Json(serde_json::Error),
+
+    /// Some data, such as a manifest or a response of the wiki, has an unexpected format.
+    Parse(String),
+
+    /// Something that was looked up, such as a cached response, does not exist.
+    NotFound(String),
+
+    /// The wiki refused to log in.
+    Login {
+        /// The name of the user.
+        user: String,
+
+        /// Why the login failed, as reported by the wiki.
+        reason: String,
+    },
+
+    /// The index of tasks could not be created.
+    Index(IndexError),
+
+    /// A batch of tasks could not be fetched from the wiki.
+    Fetch(FetchError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::Http(ref err) => write!(f, "{}", err),
+            Error::Status {
+                ref url,
+                ref status,
+            } => write!(f, "request to {} failed: {}", url, status),
+            Error::Json(ref err) => write!(f, "{}", err),
+            Error::Parse(ref message) | Error::NotFound(ref message) => f.write_str(message),
+            Error::Login {
+                ref user,
+                ref reason,
+            } => write!(f, "could not log in to the wiki as {}: {}", user, reason),
+            Error::Index(ref err) => write!(f, "{}", err),
+            Error::Fetch(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Io(_) => "could not read or write a file",
+            Error::Http(_) => "request failed",
+            Error::Status { .. } => "error status",
+            Error::Json(_) => "unexpected JSON",
+            Error::Parse(_) => "unexpected format",
+            Error::NotFound(_) => "not found",
+            Error::Login { .. } => "could not log in",
+            Error::Index(ref err) => error::Error::description(err),
+            Error::Fetch(ref err) => error::Error::description(err),
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::Http(ref err) => Some(err),
+            Error::Json(ref err) => Some(err),
+            Error::Index(ref err) => Some(err),
+            Error::Fetch(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Error {
+        Error::Http(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Error {
+        Error::Json(err)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Error {
+        Error::Parse(err.to_string())
+    }
+}
+
+impl From<UrlError> for Error {
+    fn from(err: UrlError) -> Error {
+        Error::Parse(err.to_string())
+    }
+}
+
+impl From<IndexError> for Error {
+    fn from(err: IndexError) -> Error {
+        Error::Index(err)
+    }
+}
+
+impl From<FetchError> for Error {
+    fn from(err: FetchError) -> Error {
+        Error::Fetch(err)
+    }
+}
+
+/// The reasons why a `TaskIndex` could not be created.
+#[derive(Debug)]
+pub enum IndexError {
+    /// There is no `Cargo.toml` in the workspace directory.
+    MissingManifest(PathBuf),
+
+    /// Cargo could not read the metadata of the workspace.
+    Metadata(String),
+
+    /// A file in the repository, such as a task's manifest or source code, could not be read.
+    UnreadableFile {
+        /// The path to the file.
+        path: PathBuf,
+
+        /// A description of the underlying error.
+        cause: String,
+    },
+
+    /// Some tasks in the repository do not correspond to any page on the wiki.
+    UnmatchedTasks(Vec<String>),
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IndexError::MissingManifest(ref path) => {
+                write!(f, "no workspace manifest found at {}", path.display())
+            }
+            IndexError::Metadata(ref cause) => {
+                write!(f, "could not read workspace metadata: {}", cause)
+            }
+            IndexError::UnreadableFile {
+                ref path,
+                ref cause,
+            } => write!(f, "could not read {}: {}", path.display(), cause),
+            IndexError::UnmatchedTasks(ref titles) => write!(
+                f,
+                "could not match some local tasks to tasks on the wiki: {}",
+                titles.join(", ")
+            ),
+        }
+    }
+}
+
+impl error::Error for IndexError {
+    fn description(&self) -> &str {
+        match *self {
+            IndexError::MissingManifest(_) => "missing workspace manifest",
+            IndexError::Metadata(_) => "could not read workspace metadata",
+            IndexError::UnreadableFile { .. } => "unreadable file",
+            IndexError::UnmatchedTasks(_) => "unmatched local tasks",
+        }
+    }
+}
+
+/// The kinds of failures that can happen while fetching tasks from the wiki.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchErrorKind {
+    /// The request to the wiki failed.
+    Network,
+
+    /// The response of the wiki could not be parsed.
+    Parse,
+
+    /// Any other failure, such as an error status from the wiki.
+    Other,
+}
+
+/// A batch of tasks could not be fetched from the wiki. The tasks of the batch are skipped, but
+/// later batches can still be fetched.
+#[derive(Debug)]
+pub struct FetchError {
+    /// The titles of the tasks that could not be fetched.
+    pub titles: Vec<String>,
+
+    /// What kind of failure happened.
+    pub kind: FetchErrorKind,
+
+    /// A description of the underlying error.
+    pub message: String,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "could not fetch {}: {}",
+            self.titles.join(", "),
+            self.message
+        )
+    }
+}
+
+impl error::Error for FetchError {
+    fn description(&self) -> &str {
+        match self.kind {
+            FetchErrorKind::Network => "request to the wiki failed",
+            FetchErrorKind::Parse => "could not parse the response of the wiki",
+            FetchErrorKind::Other => "could not fetch tasks",
+        }
+    }
+}
@@ -8,46 +8,73 @@
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
+extern crate log;
+#[macro_use]
 extern crate percent_encoding;
 #[macro_use]
 extern crate serde_derive;
 
 extern crate cargo_metadata;
-extern crate failure;
+extern crate flate2;
+extern crate glob;
 extern crate pathdiff;
 extern crate regex;
 extern crate reqwest;
 extern crate serde;
 extern crate serde_json;
+extern crate sha2;
 extern crate toml;
 extern crate walkdir;
 
-extern crate find_unimplemented_tasks;
-
 // Used by the test_sort macro.
 #[doc(hidden)]
 pub extern crate rand;
 
 mod remote;
+#[cfg(test)]
+mod temp_dir;
 
+use std::cell::RefCell;
 use std::cmp;
-use std::collections::{HashSet, VecDeque};
-use std::fs::File;
-use std::io::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::env;
+use std::fmt;
 use std::iter::FromIterator;
 use std::ops::Sub;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
 
-use failure::Error;
 use regex::Regex;
-use reqwest::{Client, Url};
+use reqwest::Url;
+use sha2::{Digest, Sha256};
 
+pub mod audit;
+pub mod client;
+pub mod diff;
+pub mod error;
 pub mod local;
+pub mod report;
+pub mod sample;
+pub mod sort;
+pub mod summary;
 
 #[macro_use]
 pub mod test_utils;
 
-use local::LocalTask;
+pub use client::{
+    CacheStats, CachingClient, HostLimits, HttpClient, MockWikiClient, RevisionCache, WikiClient,
+};
+pub use diff::{Change, DiffKind, HunkKind};
+pub use error::{Error, FetchError, FetchErrorKind, IndexError};
+pub use local::{slugify, SourceFormat, SourceFormats};
+pub use remote::ExtractionMethod;
+pub use report::CoverageReport;
+pub use sort::SortOrder;
+pub use summary::Summary;
+
+use local::{IgnorePatterns, LocalTask, ScanOptions};
 use remote::{RemoteTask, Response};
 
 /// Crate-specific error type.
@@ -59,12 +86,69 @@ lazy_static! {
         Regex::new(r"^https?://rosettacode\.org/wiki/([^#]+)$").unwrap();
 }
 
+/// The different ways that the URL of a task can be displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlStyle {
+    /// The canonical HTTPS URL of the task page, such as
+    /// `https://rosettacode.org/wiki/100_doors`.
+    Canonical,
+
+    /// Just the path of the task page on the wiki, such as `/wiki/100_doors`.
+    WikiPath,
+
+    /// The URL of a MediaWiki API query that returns the content of the task page.
+    Api,
+}
+
+impl UrlStyle {
+    /// The string representations of every variant, as accepted by `FromStr`.
+    pub const VALUES: &'static [&'static str] = &["canonical", "wiki-path", "api"];
+}
+
+impl Default for UrlStyle {
+    fn default() -> Self {
+        UrlStyle::Canonical
+    }
+}
+
+impl FromStr for UrlStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<UrlStyle, String> {
+        match s {
+            "canonical" => Ok(UrlStyle::Canonical),
+            "wiki-path" => Ok(UrlStyle::WikiPath),
+            "api" => Ok(UrlStyle::Api),
+            _ => Err(format!(
+                "unknown URL style '{}', expected one of {}",
+                s,
+                UrlStyle::VALUES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for UrlStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            UrlStyle::Canonical => "canonical",
+            UrlStyle::WikiPath => "wiki-path",
+            UrlStyle::Api => "api",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
 /// A representation of a Rosetta Code task. Contains information about the implementation on both
 /// the local repository and the wiki.
 #[derive(Debug, Clone)]
 pub struct Task {
     local: Option<LocalTask>,
     remote: RemoteTask,
+
+    /// The last changeset computed by `changeset`, along with the local code it was computed for.
+    changeset: RefCell<Option<(String, Rc<Vec<Change>>)>>,
 }
 
 impl Task {
@@ -75,17 +159,16 @@ impl Task {
 
     /// Returns the implementation of the task in the local repository, if it exists.
     pub fn local_code(&self) -> Option<String> {
-        self.local.clone().map(|task| {
-            // FIXME: Too simple for the multiple source file case.
-            let mut code = String::new();
-
-            for source in &task.source {
-                let mut file = File::open(source).unwrap();
-                file.read_to_string(&mut code).unwrap();
-            }
+        self.local.as_ref().map(LocalTask::code)
+    }
 
-            code
-        })
+    /// Returns the path of the main source file of the local implementation, if it exists. This is
+    /// the first of the source files, in the order their code is concatenated by `local_code`.
+    pub fn local_source_path(&self) -> Option<&Path> {
+        self.local
+            .as_ref()
+            .and_then(|local| local.source.iter().min())
+            .map(PathBuf::as_path)
     }
 
     /// Returns the implementation of the task on the wiki, if it exists.
@@ -93,6 +176,223 @@ impl Task {
         self.remote.source()
     }
 
+    /// Compares the local code against `code` instead of the code on the wiki, such as the code
+    /// of an older revision of the task's page. `None` treats the task as unimplemented on the
+    /// wiki.
+    pub fn with_remote_code(self, code: Option<String>) -> Task {
+        Task {
+            local: self.local,
+            remote: self.remote.with_source(code),
+            changeset: RefCell::new(None),
+        }
+    }
+
+    /// Returns every Rust code block on the page of the task, along with a name for each, such as
+    /// `Version 2` for a block under a `===Version 2===` subheader.
+    ///
+    /// `remote_code` only returns the first block, while this returns all of them, for pages
+    /// with several versions of the code.
+    pub fn remote_variants(&self) -> Vec<(String, String)> {
+        self.remote.variants()
+    }
+
+    /// Like `remote_variants`, but leaves out the variants that are nearly identical to an earlier
+    /// variant, such as a copy with a trivial change. See `remote::dedup_variants` for the meaning
+    /// of `threshold`.
+    pub fn distinct_remote_variants(&self, threshold: f64) -> Vec<(String, String)> {
+        remote::dedup_variants(self.remote.variants(), threshold)
+    }
+
+    /// Returns how the code of the task was extracted from its page on the wiki, or `None` if the
+    /// task isn't implemented on the wiki.
+    pub fn extraction_method(&self) -> Option<ExtractionMethod> {
+        self.remote.extraction_method()
+    }
+
+    /// Returns the checksum of the local implementation of the task, if it exists. See `checksum`.
+    pub fn local_checksum(&self) -> Option<String> {
+        self.local_code().map(|code| checksum(&code))
+    }
+
+    /// Returns the checksum of the code extracted from the wiki, if it exists. See `checksum`.
+    pub fn remote_checksum(&self) -> Option<String> {
+        self.remote.source().map(|code| checksum(&code))
+    }
+
+    /// Returns the size of the local implementation of the task in bytes, if it exists.
+    pub fn local_size_bytes(&self) -> Option<usize> {
+        self.local_code().map(|code| code.len())
+    }
+
+    /// Returns the number of lines of the local implementation of the task, if it exists.
+    pub fn local_line_count(&self) -> Option<usize> {
+        self.local_code().map(|code| code.lines().count())
+    }
+
+    /// Returns the time that the local implementation of the task was last modified, if it
+    /// exists.
+    pub fn local_modified(&self) -> Option<SystemTime> {
+        self.local.as_ref().and_then(LocalTask::modified)
+    }
+
+    /// Returns the size of the code extracted from the wiki in bytes, if it exists.
+    ///
+    /// This only counts the Rust code itself. See `remote_page_size_bytes` for the size of the
+    /// whole page.
+    pub fn remote_size_bytes(&self) -> Option<usize> {
+        self.remote.source().map(|code| code.len())
+    }
+
+    /// Returns the size of the wiki markup of the entire task page in bytes.
+    pub fn remote_page_size_bytes(&self) -> usize {
+        self.remote.page_size()
+    }
+
+    /// Returns the crates that are referenced by the local implementation of the task, but aren't
+    /// declared as dependencies in its manifest.
+    ///
+    /// Returns an empty list if the task isn't implemented locally.
+    pub fn undeclared_dependencies(&self) -> Vec<String> {
+        match (&self.local, self.local_code()) {
+            (&Some(ref local), Some(ref code)) => local::referenced_crates(code)
+                .into_iter()
+                .filter(|name| !local.dependencies.contains(name))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns whether the local implementation of the task declares a `main` function.
+    ///
+    /// Returns `None` if the task isn't implemented locally, or if it is implemented as a library,
+    /// since libraries aren't expected to be runnable.
+    pub fn has_main(&self) -> Option<bool> {
+        let local = self.local.as_ref()?;
+        let code = self.local_code()?;
+
+        if local::is_library_task(&local.source, &code) {
+            return None;
+        }
+
+        Some(local::defines_main(&code))
+    }
+
+    /// Returns the languages that the task is implemented in on the wiki, including Rust, in the
+    /// order of their sections on the page.
+    pub fn languages(&self) -> Vec<String> {
+        self.remote.languages()
+    }
+
+    /// Returns the number of languages that the task is implemented in on the wiki, including
+    /// Rust. This is a rough measure of how popular the task is.
+    pub fn implementation_count(&self) -> usize {
+        self.remote.implementation_count()
+    }
+
+    /// Classifies how the local implementation of the task differs from the one on the wiki.
+    ///
+    /// Differences that only consist of trailing whitespace or newlines are reported as
+    /// `DiffKind::WhitespaceOnly`, and differences in indentation as `DiffKind::IndentationOnly`,
+    /// so they can be told apart from substantive changes.
+    pub fn diff_kind(&self) -> DiffKind {
+        diff::diff_kind(
+            self.local_code().as_ref().map(String::as_str),
+            self.remote_code().as_ref().map(String::as_str),
+        )
+    }
+
+    /// Returns the changes that turn the remote code of the task into the local code, so that
+    /// diffs can be rendered without comparing the code again.
+    ///
+    /// The changeset is cached. The local code is read again on every call, so the changeset is
+    /// only recomputed if the local code changed in the meantime. Returns `None` unless the task is
+    /// implemented both locally and on the wiki.
+    pub fn changeset(&self) -> Option<Rc<Vec<Change>>> {
+        let remote_code = self.remote_code()?;
+        let local_code = self.local_code()?;
+
+        let mut cache = self.changeset.borrow_mut();
+        if let Some((ref code, ref changeset)) = *cache {
+            if *code == local_code {
+                return Some(Rc::clone(changeset));
+            }
+        }
+
+        let changeset = Rc::new(diff::changeset(&remote_code, &local_code));
+        *cache = Some((local_code, Rc::clone(&changeset)));
+        Some(changeset)
+    }
+
+    /// Returns how similar the local and remote code of the task are, from `0.0` to `1.0`. See
+    /// `diff::similarity` for how the ratio is computed.
+    ///
+    /// Unlike the number of changed lines, the ratio can be compared between tasks of different
+    /// sizes. Returns `None` unless the task is implemented both locally and on the wiki.
+    pub fn diff_ratio(&self) -> Option<f64> {
+        match (self.remote_code(), self.local_code()) {
+            (Some(remote_code), Some(local_code)) => {
+                Some(diff::similarity(&remote_code, &local_code))
+            }
+            _ => None,
+        }
+    }
+
+    /// True if and only if the task is implemented both locally and on the wiki, and the
+    /// implementations are not identical.
+    ///
+    /// Differences in whitespace count as divergence. Compare `diff_kind` against
+    /// `DiffKind::Substantive` to ignore them.
+    pub fn is_diverged(&self) -> bool {
+        match self.diff_kind() {
+            DiffKind::WhitespaceOnly | DiffKind::IndentationOnly | DiffKind::Substantive => true,
+            DiffKind::Identical | DiffKind::Missing => false,
+        }
+    }
+
+    /// Returns a short, single-line description of the status of the task, such as
+    /// `K-d tree [local ✓ remote ✓ diff:12]`.
+    ///
+    /// The diff count is the number of lines that differ between the local and remote code, and
+    /// is only included if the task is implemented on both sides. The string contains no terminal
+    /// escape codes, so it can be piped and searched.
+    pub fn summary(&self) -> String {
+        fn mark(present: bool) -> &'static str {
+            if present {
+                "✓"
+            } else {
+                "✗"
+            }
+        }
+
+        let local_code = self.local_code();
+        let remote_code = self.remote_code();
+
+        let mut summary = format!(
+            "{} [local {} remote {}",
+            self.title(),
+            mark(local_code.is_some()),
+            mark(remote_code.is_some())
+        );
+
+        if let (Some(local_code), Some(remote_code)) = (local_code, remote_code) {
+            summary.push_str(&format!(
+                " diff:{}",
+                diff::changed_lines(&remote_code, &local_code)
+            ));
+        }
+
+        summary.push(']');
+        summary
+    }
+
+    /// Returns the number of lines that the local code adds and removes compared to the code on
+    /// the wiki, in that order. Returns `None` unless the task is implemented both locally and on
+    /// the wiki. See `changeset`.
+    pub fn line_changes(&self) -> Option<(usize, usize)> {
+        self.changeset()
+            .map(|changeset| diff::line_changes(&changeset))
+    }
+
     /// True if and only if the task is only implemented on the Rosetta Code wiki.
     pub fn is_remote_only(&self) -> bool {
         self.local.is_none() && self.remote.source().is_some()
@@ -109,11 +409,59 @@ impl Task {
         self.local.is_none() && self.remote.source().is_none()
     }
 
+    /// True if the page of the task on the wiki has a Rust section, even if no code could be
+    /// extracted from it.
+    pub fn has_rust_section(&self) -> bool {
+        self.remote.has_rust_section()
+    }
+
+    /// True if the page of the task has a Rust section, but no Rust code could be extracted from
+    /// it. This usually means that the section uses markup that the parser does not understand.
+    pub fn is_unparseable(&self) -> bool {
+        self.remote.is_unparseable()
+    }
+
     /// Returns the URL of the task on the Rosetta Code wiki.
     pub fn url(&self) -> Url {
         self.remote.url()
     }
 
+    /// Returns the description of the task on the wiki as plain text, if its page has one.
+    pub fn description(&self) -> Option<String> {
+        self.remote.description()
+    }
+
+    /// Returns the URL that edits the Rust section of the task on the wiki. If the task has no Rust
+    /// section, the URL edits the whole page.
+    pub fn edit_url(&self) -> Url {
+        self.remote.edit_url()
+    }
+
+    /// Returns the URL of the task rendered in the given style.
+    pub fn url_with_style(&self, style: UrlStyle) -> String {
+        let mut url = self.url();
+
+        match style {
+            UrlStyle::Canonical => {
+                url.set_scheme("https").unwrap();
+                url.to_string()
+            }
+            UrlStyle::WikiPath => url.path().to_owned(),
+            UrlStyle::Api => {
+                let mut request = Url::parse(remote::API_URL).unwrap();
+                request.set_scheme("https").unwrap();
+                request
+                    .query_pairs_mut()
+                    .append_pair("action", "query")
+                    .append_pair("format", "json")
+                    .append_pair("prop", "revisions")
+                    .append_pair("rvprop", "content")
+                    .append_pair("titles", &self.title());
+                request.to_string()
+            }
+        }
+    }
+
     /// Returns the relative path to the task from repository root.
     pub fn local_path(&self) -> Option<PathBuf> {
         self.local.as_ref().and_then(|l| {
@@ -121,51 +469,626 @@ impl Task {
             pathdiff::diff_paths(l.manifest_path.parent().unwrap(), workspace_root_dir)
         })
     }
+
+    /// Returns the absolute path to the directory of the task's crate, if it is implemented
+    /// locally.
+    pub fn manifest_dir(&self) -> Option<&Path> {
+        self.local.as_ref().and_then(|l| l.manifest_path.parent())
+    }
+}
+
+/// Computes the checksum of some code, as returned by `Task::local_checksum` and
+/// `Task::remote_checksum`.
+///
+/// The checksum is the SHA-256 hash of the UTF-8 bytes of the code, written as 64 lowercase
+/// hexadecimal digits. It only depends on the code, so it can be compared between runs and
+/// versions to detect changes.
+pub fn checksum(code: &str) -> String {
+    let mut hasher = Sha256::default();
+    hasher.input(code.as_bytes());
+
+    hasher
+        .result()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Configures how a `TaskIndex` is created.
+///
+/// By default, the repository is scanned from scratch and the wiki is queried over the network
+/// without caching.
+///
+/// ```no_run
+/// # use meta::TaskIndexBuilder;
+/// # use std::time::Duration;
+/// let index = TaskIndexBuilder::new("/path/to/rust-rosetta")
+///     .index_cache("/tmp/coverage-index.json")
+///     .http_cache("/tmp/http-cache", Duration::from_secs(60 * 60))
+///     .build()
+///     .unwrap();
+/// ```
+pub struct TaskIndexBuilder {
+    workspace_root: PathBuf,
+    index_cache: Option<PathBuf>,
+    client: Option<Box<WikiClient>>,
+    http_cache: Option<(PathBuf, Duration)>,
+    revision_cache: Option<PathBuf>,
+    source_formats: SourceFormats,
+    ignore_patterns: Vec<String>,
+}
+
+impl TaskIndexBuilder {
+    /// Creates a builder for the workspace in `workspace_root`, the directory that contains the
+    /// workspace manifest.
+    pub fn new<P: Into<PathBuf>>(workspace_root: P) -> TaskIndexBuilder {
+        TaskIndexBuilder {
+            workspace_root: workspace_root.into(),
+            index_cache: None,
+            client: None,
+            http_cache: None,
+            revision_cache: None,
+            source_formats: SourceFormats::default(),
+            ignore_patterns: vec![],
+        }
+    }
+
+    /// Reuses the local tasks stored in the cache file at `path` if nothing in the repository has
+    /// changed since it was written. The cache is rewritten whenever the repository has to be
+    /// scanned again.
+    pub fn index_cache<P: Into<PathBuf>>(mut self, path: P) -> TaskIndexBuilder {
+        self.index_cache = Some(path.into());
+        self
+    }
+
+    /// Performs all requests to the wiki with the given client, instead of an `HttpClient`.
+    pub fn client<C: WikiClient + 'static>(mut self, client: C) -> TaskIndexBuilder {
+        self.client = Some(Box::new(client));
+        self
+    }
+
+    /// Caches the responses of the wiki in `dir` for `ttl`. See `CachingClient`.
+    pub fn http_cache<P: Into<PathBuf>>(mut self, dir: P, ttl: Duration) -> TaskIndexBuilder {
+        self.http_cache = Some((dir.into(), ttl));
+        self
+    }
+
+    /// Stores the pages of fetched tasks in `dir` by revision, and reuses a stored page as long as
+    /// it is the latest revision on the wiki. The IDs of the latest revisions are requested before
+    /// each batch of pages, which is much cheaper than downloading the pages. See
+    /// `RevisionCache`.
+    ///
+    /// The IDs of the latest revisions are never taken from the `http_cache`, so the pages are
+    /// never stale.
+    pub fn revision_cache<P: Into<PathBuf>>(mut self, dir: P) -> TaskIndexBuilder {
+        self.revision_cache = Some(dir.into());
+        self
+    }
+
+    /// Reads task code from files with the given extension (without the leading dot), extracting
+    /// it according to `format`. By default, only `.rs` files are read.
+    ///
+    /// For example, `source_format("md", SourceFormat::Markdown)` also tracks tasks whose code is
+    /// in fenced `rust` code blocks of Markdown files.
+    pub fn source_format<S: Into<String>>(
+        mut self,
+        extension: S,
+        format: SourceFormat,
+    ) -> TaskIndexBuilder {
+        self.source_formats = self.source_formats.with_extension(extension, format);
+        self
+    }
+
+    /// Skips the files and directories that match a gitignore-style pattern when the workspace is
+    /// scanned, in addition to the patterns in `.rosettaignore`. See `local::IgnorePatterns`.
+    pub fn ignore<S: Into<String>>(mut self, pattern: S) -> TaskIndexBuilder {
+        self.ignore_patterns.push(pattern.into());
+        self
+    }
+
+    /// Creates the index by parsing tasks in the repository and requesting task titles from the
+    /// wiki.
+    ///
+    /// If the repository can't be read, the error will be an `Error::Index` describing why.
+    pub fn build(self) -> Result<TaskIndex> {
+        // The default client is cached before it is boxed, so that it can revalidate cached
+        // responses with conditional requests.
+        let client: Box<WikiClient> = match (self.client, self.http_cache) {
+            (None, Some((dir, ttl))) => Box::new(CachingClient::new(HttpClient::new(), dir, ttl)),
+            (Some(client), Some((dir, ttl))) => Box::new(CachingClient::new(client, dir, ttl)),
+            (None, None) => Box::new(HttpClient::new()),
+            (Some(client), None) => client,
+        };
+
+        let manifest_path = self.workspace_root.join("Cargo.toml");
+        let mut options = ScanOptions {
+            formats: self.source_formats,
+            ignore: IgnorePatterns::read(&self.workspace_root)?,
+        };
+        for pattern in &self.ignore_patterns {
+            options.ignore.add(pattern)?;
+        }
+
+        let local_tasks = match self.index_cache {
+            Some(ref path) => local::parse_tasks_cached(&manifest_path, path, &options)?,
+            None => local::parse_tasks_with(&manifest_path, &options)?,
+        };
+
+        let mut index = TaskIndex::from_local_tasks(self.workspace_root, local_tasks, client)?;
+        index.ignore = options.ignore;
+        index.revision_cache = self.revision_cache.map(RevisionCache::new);
+        index.omitted_titles = local::parse_omitted_titles(&manifest_path)?;
+        index.aliases = local::parse_aliases(&manifest_path)?;
+        Ok(index)
+    }
+}
+
+/// Tells `TaskIndex::for_each_task` whether to keep processing tasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// Continue with the next task.
+    Continue,
+
+    /// Stop processing tasks. No further requests will be made to the wiki.
+    Break,
+}
+
+/// The resolved status of a single task, as reported by `TaskIndex::for_each_task`.
+#[derive(Debug, Clone)]
+pub struct TaskStatus {
+    /// The title of the task.
+    pub title: String,
+
+    /// True if the task is implemented in the local repository.
+    pub implemented_locally: bool,
+
+    /// True if the task is implemented on the wiki.
+    pub implemented_remotely: bool,
+
+    /// How the local implementation differs from the one on the wiki.
+    pub diff_kind: DiffKind,
+
+    /// The task itself, for access to its code and other details.
+    pub task: Task,
+}
+
+impl TaskStatus {
+    fn new(task: Task) -> TaskStatus {
+        TaskStatus {
+            title: task.title(),
+            implemented_locally: task.local.is_some(),
+            implemented_remotely: task.remote.source().is_some(),
+            diff_kind: task.diff_kind(),
+            task,
+        }
+    }
+}
+
+/// Whether a task is implemented locally and on the wiki, as reported by
+/// `TaskIndex::fetch_status` without the code of the task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskPresence {
+    /// The title of the task.
+    pub title: String,
+
+    /// True if the task is implemented in the local repository.
+    pub implemented_locally: bool,
+
+    /// True if the page of the task has a Rust section on the wiki.
+    pub implemented_remotely: bool,
+}
+
+impl TaskPresence {
+    /// Returns a one-line summary of the task, in the same format as `Task::summary`.
+    pub fn summary(&self) -> String {
+        let mark = |present| if present { "✓" } else { "✗" };
+
+        format!(
+            "{} [local {} remote {}]",
+            self.title,
+            mark(self.implemented_locally),
+            mark(self.implemented_remotely)
+        )
+    }
 }
 
 /// The index of all tasks implemented locally and remotely.
+///
+/// Tasks can be intentionally excluded from the repository by listing their titles in the `omit`
+/// key of the `[package.metadata.rosettacode]` table of the workspace manifest. Omitted tasks are
+/// left out of `all_titles` and `fetch_all_tasks`. Requesting an omitted task by name with
+/// `fetch_tasks` takes precedence over the omit list, so the task is fetched anyway.
+///
+/// Tasks can also be requested by the short names listed in the
+/// `[package.metadata.rosettacode.aliases]` table, such as `qsort` for
+/// `Sorting algorithms/Quicksort`.
 pub struct TaskIndex {
+    workspace_root: PathBuf,
     all_task_titles: HashSet<String>,
+    omitted_titles: BTreeSet<String>,
+    aliases: BTreeMap<String, String>,
+    ignore: IgnorePatterns,
     local_tasks: Vec<LocalTask>,
-    client: Client,
+    client: Box<WikiClient>,
+    revision_cache: Option<RevisionCache>,
 }
 
 impl TaskIndex {
     /// Generate the task index by parsing tasks in the repository and requesting task titles from
     /// the wiki.
+    ///
+    /// If the repository can't be read, the error will be an `Error::Index` describing why.
+    /// Use `TaskIndex::builder` to configure how the index is created.
     pub fn create<P: AsRef<Path>>(workspace_root: P) -> Result<TaskIndex> {
-        let local_tasks = local::parse_tasks(workspace_root.as_ref().join("Cargo.toml")).unwrap();
+        Self::builder(workspace_root.as_ref()).build()
+    }
+
+    /// Generate the task index like `create`, but reuse the local tasks stored in the cache file
+    /// at `cache_path` if nothing in the repository has changed since it was written. The cache is
+    /// rewritten whenever the repository has to be scanned again.
+    pub fn create_cached<P, Q>(workspace_root: P, cache_path: Q) -> Result<TaskIndex>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        Self::builder(workspace_root.as_ref())
+            .index_cache(cache_path.as_ref())
+            .build()
+    }
+
+    /// Generate the task index like `create_cached`, but perform all requests to the wiki with
+    /// the given client.
+    pub fn create_cached_with_client<P, Q, C>(
+        workspace_root: P,
+        cache_path: Q,
+        client: C,
+    ) -> Result<TaskIndex>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        C: WikiClient + 'static,
+    {
+        Self::builder(workspace_root.as_ref())
+            .index_cache(cache_path.as_ref())
+            .client(client)
+            .build()
+    }
+
+    /// Generate the task index like `create`, but perform all requests to the wiki with the
+    /// given client.
+    pub fn create_with_client<P, C>(workspace_root: P, client: C) -> Result<TaskIndex>
+    where
+        P: AsRef<Path>,
+        C: WikiClient + 'static,
+    {
+        Self::builder(workspace_root.as_ref())
+            .client(client)
+            .build()
+    }
+
+    /// Returns a builder that creates the index for the workspace in `workspace_root`, the
+    /// directory that contains the workspace manifest.
+    pub fn builder<P: Into<PathBuf>>(workspace_root: P) -> TaskIndexBuilder {
+        TaskIndexBuilder::new(workspace_root)
+    }
+
+    fn from_local_tasks(
+        workspace_root: PathBuf,
+        local_tasks: Vec<LocalTask>,
+        client: Box<WikiClient>,
+    ) -> Result<TaskIndex> {
         let local_task_titles = local_tasks.iter().map(|task| task.title.clone()).collect();
 
-        let client = Client::new();
-        let all_task_titles = TaskIndex::all_task_titles(&client, &local_task_titles)?;
+        let all_task_titles = TaskIndex::all_task_titles(&*client, &local_task_titles)?;
 
         Ok(TaskIndex {
+            workspace_root,
             all_task_titles,
+            omitted_titles: BTreeSet::new(),
+            aliases: BTreeMap::new(),
+            ignore: IgnorePatterns::new(),
             local_tasks,
             client,
+            revision_cache: None,
         })
     }
 
+    /// Returns the title of every task known to the index, in the order that `fetch_all_tasks`
+    /// would process them. Omitted tasks are not included.
+    ///
+    /// The titles are gathered when the index is created, so this method works offline and never
+    /// performs any requests to the wiki.
+    pub fn all_titles<'a>(&'a self) -> impl Iterator<Item = &'a str> + 'a {
+        let mut titles = self.all_task_titles
+            .iter()
+            .filter(|title| !self.omitted_titles.contains(*title))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        titles.sort_by(|a, b| sort::compare_titles(a, b));
+        titles.into_iter()
+    }
+
+    /// Returns the titles of the tasks that are omitted from the repository, in sorted order.
+    pub fn omitted_titles<'a>(&'a self) -> impl Iterator<Item = &'a str> + 'a {
+        self.omitted_titles.iter().map(String::as_str)
+    }
+
+    /// True if the task with the given title is omitted from the repository.
+    pub fn is_omitted(&self, title: &str) -> bool {
+        self.omitted_titles.contains(title)
+    }
+
+    /// Returns the aliases of tasks, mapped to the titles of the tasks.
+    pub fn aliases(&self) -> &BTreeMap<String, String> {
+        &self.aliases
+    }
+
+    /// Returns the title of the task that an alias refers to, or the name itself if it isn't an
+    /// alias.
+    pub fn resolve_alias<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map_or(name, String::as_str)
+    }
+
+    /// Requests the categories of every task from the wiki, and returns the titles of the tasks in
+    /// each category.
+    ///
+    /// Omitted tasks are not included, and neither are the categories that list all tasks. Tasks
+    /// without any other category are grouped under `Uncategorized`.
+    pub fn categories(&self) -> Result<BTreeMap<String, Vec<String>>> {
+        const TASK_CATEGORIES: &[&str] = &["Programming Tasks", "Draft Programming Tasks"];
+
+        let titles = self.all_titles().map(String::from).collect::<Vec<_>>();
+        let page_categories =
+            remote::page_categories(&titles, |request| client::get_json(&*self.client, request))?;
+
+        let mut categories = BTreeMap::new();
+
+        for (title, page_categories) in page_categories {
+            let mut page_categories = page_categories
+                .into_iter()
+                .filter(|category| !TASK_CATEGORIES.contains(&category.as_str()))
+                .collect::<Vec<_>>();
+
+            if page_categories.is_empty() {
+                page_categories.push(String::from("Uncategorized"));
+            }
+
+            for category in page_categories {
+                categories
+                    .entry(category)
+                    .or_insert_with(Vec::new)
+                    .push(title.clone());
+            }
+        }
+
+        Ok(categories)
+    }
+
+    /// Requests the ID of the latest revision of each of the given tasks from the wiki, or of
+    /// every task if no titles are given. The content of the pages is not downloaded, so this is
+    /// a cheap way to find out which tasks changed on the wiki.
+    ///
+    /// Tasks that don't exist on the wiki are left out.
+    pub fn latest_revisions(&self, titles: &[String]) -> Result<BTreeMap<String, u64>> {
+        let titles = if titles.is_empty() {
+            self.all_titles().map(String::from).collect()
+        } else {
+            titles.to_vec()
+        };
+
+        remote::latest_revisions(&titles, |request| client::get_json(&*self.client, request))
+    }
+
+    /// Requests the Rust code of tasks at the given revisions from the wiki, by the title of each
+    /// task, so that local code can be compared against older versions of the wiki.
+    ///
+    /// Tasks whose revision has no Rust code map to `None`. Revisions that don't exist are left
+    /// out.
+    pub fn revision_code(&self, revisions: &[u64]) -> Result<BTreeMap<String, Option<String>>> {
+        remote::revision_code(revisions, |request| {
+            client::get_json(&*self.client, request)
+        })
+    }
+
+    /// Returns the local tasks whose crates aren't in the directory derived from their titles,
+    /// sorted by title. Omitted tasks are accepted exceptions, so they are not checked.
+    pub fn naming_mismatches(&self) -> Vec<local::NamingMismatch> {
+        let mut mismatches = self.local_tasks
+            .iter()
+            .filter(|task| !self.is_omitted(&task.title))
+            .filter_map(|task| local::naming_mismatch(task, &self.workspace_root))
+            .collect::<Vec<_>>();
+        mismatches.sort_by(|a, b| a.title.cmp(&b.title));
+        mismatches
+    }
+
+    /// Returns how many pages were reused from the revision cache and how many were downloaded,
+    /// or `None` if the index has no revision cache. See `TaskIndexBuilder::revision_cache`.
+    pub fn revision_cache_stats(&self) -> Option<CacheStats> {
+        self.revision_cache.as_ref().map(RevisionCache::stats)
+    }
+
+    /// Counts the files in the workspace that were skipped by the ignore patterns when the index
+    /// was created.
+    pub fn ignored_files(&self) -> Result<usize> {
+        local::count_ignored(&self.workspace_root, &self.ignore)
+    }
+
+    /// Returns the markers of incomplete work in the code of each local task, such as `// TODO`
+    /// comments, by title. Tasks without markers and omitted tasks are not included. See
+    /// `LocalTask::todo_markers`.
+    pub fn todo_markers(&self) -> BTreeMap<String, Vec<local::TodoMarker>> {
+        self.local_tasks
+            .iter()
+            .filter(|task| !self.is_omitted(&task.title))
+            .map(|task| (task.title.clone(), task.todo_markers()))
+            .filter(|&(_, ref markers)| !markers.is_empty())
+            .collect()
+    }
+
+    /// Returns the titles of the local tasks in each module, sorted by title. See `local::module`
+    /// for how the module of a task is determined. Tasks that aren't in any module are not
+    /// included.
+    pub fn modules(&self) -> BTreeMap<String, Vec<String>> {
+        let mut modules = BTreeMap::new();
+
+        for task in &self.local_tasks {
+            if let Some(module) = local::module(task, &self.workspace_root) {
+                modules
+                    .entry(module)
+                    .or_insert_with(Vec::new)
+                    .push(task.title.clone());
+            }
+        }
+
+        for titles in modules.values_mut() {
+            titles.sort();
+        }
+
+        modules
+    }
+
+    /// True if the task with the given title is implemented in the local repository.
+    ///
+    /// Like `all_titles`, this never performs any requests to the wiki.
+    pub fn is_implemented_locally(&self, title: &str) -> bool {
+        self.local_tasks.iter().any(|task| task.title == title)
+    }
+
     /// Retrieves data for every task on Rosetta Code.
     pub fn fetch_all_tasks(&self) -> TaskIterator {
         Self::fetch_tasks(self, &[])
     }
 
+    /// Like `fetch_all_tasks`, but calls `progress` with the number of tasks that are done and the
+    /// total number of tasks as the iteration advances. See `TaskIterator::with_progress`.
+    pub fn fetch_all_tasks_with_progress<'a, F>(&'a self, progress: F) -> TaskIterator<'a>
+    where
+        F: FnMut(usize, usize) + 'a,
+    {
+        self.fetch_all_tasks().with_progress(progress)
+    }
+
     /// Parses both local (implemented in this repository) and remote (implemented on the wiki)
-    /// tasks, and returns the code of each.
+    /// tasks, and returns the code of each. Tasks can be requested by their aliases.
     pub fn fetch_tasks(&self, tasks: &[String]) -> TaskIterator {
-        TaskIterator::new(self, tasks)
+        let titles = tasks
+            .iter()
+            .map(|task| self.resolve_alias(task).to_owned())
+            .collect::<Vec<_>>();
+        TaskIterator::new(self, &titles)
+    }
+
+    /// Checks whether the tasks with the given titles are implemented locally and on the wiki,
+    /// without downloading their code. If no titles are given, every task is checked.
+    ///
+    /// Pages with a Rust section are in the `Rust` category of the wiki, so only the categories of
+    /// the pages are requested. This is much faster than `fetch_tasks`, but a Rust section that
+    /// doesn't contain any code counts as an implementation. Titles that aren't tasks are skipped.
+    pub fn fetch_status(&self, titles: &[String]) -> Result<Vec<TaskPresence>> {
+        let titles = if titles.is_empty() {
+            self.all_titles().map(String::from).collect::<Vec<_>>()
+        } else {
+            titles
+                .iter()
+                .map(|title| self.resolve_alias(title).to_owned())
+                .filter(|title| self.all_task_titles.contains(title))
+                .collect()
+        };
+
+        let page_categories =
+            remote::page_categories(&titles, |request| client::get_json(&*self.client, request))?;
+
+        Ok(titles
+            .into_iter()
+            .map(|title| TaskPresence {
+                implemented_locally: self.is_implemented_locally(&title),
+                implemented_remotely: page_categories
+                    .get(&title)
+                    .map_or(false, |categories| categories.contains("Rust")),
+                title,
+            })
+            .collect())
+    }
+
+    /// Calls `callback` with the status of every task that matches `filter`, as soon as the task
+    /// has been fetched.
+    ///
+    /// Unlike collecting the iterator returned by `fetch_all_tasks`, this allows consumers to
+    /// react to each task as it arrives. If the callback returns `ControlFlow::Break`, no more
+    /// tasks are fetched. Stops at the first task that could not be fetched and returns its error.
+    pub fn for_each_task<P, F>(&self, mut filter: P, mut callback: F) -> Result<()>
+    where
+        P: FnMut(&Task) -> bool,
+        F: FnMut(TaskStatus) -> ControlFlow,
+    {
+        for task in self.fetch_all_tasks() {
+            let task = task?;
+
+            if !filter(&task) {
+                continue;
+            }
+
+            if callback(TaskStatus::new(task)) == ControlFlow::Break {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the title of the task that a file in the local repository belongs to.
+    ///
+    /// `path` may be absolute, or relative to either the current directory or the workspace root.
+    /// Any file inside the crate of a task maps to that task, so tasks that are split over several
+    /// source files can be resolved from any of them. Returns `None` if the file does not belong to
+    /// a known task.
+    pub fn title_for_path(&self, path: &Path) -> Option<String> {
+        let candidates = if path.is_absolute() {
+            vec![path.to_owned()]
+        } else {
+            let mut candidates = vec![];
+            if let Ok(current_dir) = env::current_dir() {
+                candidates.push(current_dir.join(path));
+            }
+            candidates.push(self.workspace_root.join(path));
+            candidates
+        };
+
+        candidates
+            .into_iter()
+            .map(|candidate| candidate.canonicalize().unwrap_or(candidate))
+            .filter_map(|candidate| {
+                // Task crates may be nested inside each other, so prefer the innermost crate that
+                // contains the file.
+                self.local_tasks
+                    .iter()
+                    .filter_map(|task| {
+                        let crate_dir = task.manifest_path.parent().unwrap();
+                        let crate_dir = crate_dir
+                            .canonicalize()
+                            .unwrap_or_else(|_| crate_dir.to_owned());
+
+                        if candidate.starts_with(&crate_dir) {
+                            Some((crate_dir.components().count(), task))
+                        } else {
+                            None
+                        }
+                    })
+                    .max_by_key(|&(depth, _)| depth)
+                    .map(|(_, task)| task.title.clone())
+            })
+            .next()
     }
 
     fn all_task_titles(
-        client: &Client,
+        client: &WikiClient,
         local_task_titles: &HashSet<String>,
     ) -> Result<HashSet<String>> {
-        let mut all_task_titles = find_unimplemented_tasks::all_tasks()
-            .into_iter()
-            .map(|task| task.title)
-            .collect::<HashSet<_>>();
+        let all_task_titles = remote::category_members("Programming Tasks", |request| {
+            client::get_json(client, request)
+        })?;
+        let mut all_task_titles = all_task_titles.into_iter().collect::<HashSet<_>>();
 
         let all_decoded_task_titles = all_task_titles
             .iter()
@@ -181,7 +1104,7 @@ impl TaskIndex {
             possible_draft_titles.sort();
 
             // FIXME: We might have to loop here if we get too many draft tasks.
-            let mut request = Url::parse("http://rosettacode.org/mw/api.php").unwrap();
+            let mut request = Url::parse(remote::API_URL).unwrap();
             request
                 .query_pairs_mut()
                 .append_pair("action", "query")
@@ -189,7 +1112,7 @@ impl TaskIndex {
                 .append_pair("prop", "info")
                 .append_pair("titles", &possible_draft_titles.join("|"));
 
-            let response: Response = client.get(request.as_str()).send()?.json()?;
+            let response: Response = client::get_json(client, &request)?;
 
             // In the response, any missing pages will have a negative ID.
             let bad_titles = response
@@ -200,10 +1123,7 @@ impl TaskIndex {
                 .collect::<Vec<_>>();
 
             if !bad_titles.is_empty() {
-                panic!(
-                    "Could not match some local tasks to tasks on the wiki: {:?}",
-                    bad_titles
-                );
+                return Err(IndexError::UnmatchedTasks(bad_titles).into());
             }
 
             all_task_titles.extend(possible_draft_titles);
@@ -216,57 +1136,220 @@ impl TaskIndex {
 /// An iterator over tasks. Parses information from both the rust-rosetta repository and the
 /// Rosetta Code wiki.
 pub struct TaskIterator<'a> {
-    client: &'a Client,
+    client: &'a WikiClient,
+    revision_cache: Option<&'a RevisionCache>,
     fetched_remote_tasks: VecDeque<RemoteTask>,
     requested_task_titles: VecDeque<String>,
     local_tasks: Vec<LocalTask>,
+    deadline: Option<Instant>,
+    reached_deadline: bool,
+    sort_order: SortOrder,
+    total: usize,
+    progress: Option<Box<FnMut(usize, usize) + 'a>>,
 }
 
 impl<'a> TaskIterator<'a> {
     /// Creates a new iterator over the specified tasks. If no tasks are supplied, iterates over
     /// all tasks.
+    ///
+    /// Pages outside of the main namespace of the wiki, such as categories, are never part of the
+    /// index, but they are still fetched if they are requested explicitly.
     fn new(task_index: &'a TaskIndex, titles: &[String]) -> TaskIterator<'a> {
         let mut requested_task_titles: Vec<_> = if titles.is_empty() {
-            task_index.all_task_titles.iter().cloned().collect()
+            task_index.all_titles().map(String::from).collect()
         } else {
-            task_index
+            // Only the first letter of a title is case-insensitive on the wiki, so fall back to the
+            // capitalized title before treating the task as nonexistent.
+            let titles = titles
+                .iter()
+                .map(|title| {
+                    let normalized = remote::normalize_title(title);
+                    if !task_index.all_task_titles.contains(title)
+                        && task_index.all_task_titles.contains(&normalized)
+                    {
+                        info!("'{}' is not a task, using '{}' instead", title, normalized);
+                        normalized
+                    } else {
+                        title.clone()
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut requested: Vec<_> = task_index
                 .all_task_titles
                 .intersection(&HashSet::from_iter(titles.iter().cloned()))
                 .cloned()
-                .collect()
+                .collect();
+            requested.extend(
+                titles
+                    .iter()
+                    .filter(|title| remote::namespace(title).is_some())
+                    .cloned(),
+            );
+            requested
         };
 
-        requested_task_titles.sort();
+        let sort_order = SortOrder::default();
+        requested_task_titles.sort_by(|a, b| sort_order.compare(a, b));
+        let total = requested_task_titles.len();
 
         TaskIterator {
-            client: &task_index.client,
+            client: &*task_index.client,
+            revision_cache: task_index.revision_cache.as_ref(),
             fetched_remote_tasks: Default::default(),
             requested_task_titles: requested_task_titles.into_iter().collect(),
             local_tasks: task_index.local_tasks.clone(),
+            deadline: None,
+            reached_deadline: false,
+            sort_order,
+            total,
+            progress: None,
         }
     }
 
-    /// Fetch the next set of remote tasks.
+    /// Stops the iteration once the deadline has passed. The deadline is checked before each
+    /// task, so no more requests are made after it has passed.
+    pub fn with_deadline(mut self, deadline: Instant) -> TaskIterator<'a> {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Yields the tasks in the given order instead of the default `SortOrder::Alpha`. This must
+    /// be called before the iteration starts.
+    pub fn with_sort_order(mut self, sort_order: SortOrder) -> TaskIterator<'a> {
+        let mut titles = self.requested_task_titles.drain(..).collect::<Vec<_>>();
+        titles.sort_by(|a, b| sort_order.compare(a, b));
+
+        self.requested_task_titles = titles.into_iter().collect();
+        self.sort_order = sort_order;
+        self
+    }
+
+    /// Calls `progress` with the number of tasks that are done and the total number of requested
+    /// tasks after each task is yielded, or a batch of tasks fails, so that embedders can render
+    /// their own progress. Requested pages that don't exist count as done, so the count reaches
+    /// the total at the end of the iteration, unless it stops at the deadline.
+    ///
+    /// Tasks are fetched on the thread that advances the iterator, which is also the only thread
+    /// that `progress` is called on.
+    pub fn with_progress<F>(mut self, progress: F) -> TaskIterator<'a>
+    where
+        F: FnMut(usize, usize) + 'a,
+    {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// True if the iteration was stopped because the deadline passed.
+    pub fn reached_deadline(&self) -> bool {
+        self.reached_deadline
+    }
+
+    /// The number of tasks that have not been yielded yet.
+    pub fn remaining(&self) -> usize {
+        self.requested_task_titles.len() + self.fetched_remote_tasks.len()
+    }
+
+    /// Passes the number of tasks that are done to the progress callback, if there is one.
+    fn report_progress(&mut self) {
+        let done = self.total.saturating_sub(self.remaining());
+        let total = self.total;
+
+        if let Some(ref mut progress) = self.progress {
+            progress(done, total);
+        }
+    }
+
+    /// Fetch the next set of remote tasks. If the batch can't be fetched, returns a `FetchError`
+    /// with the titles of the batch.
     fn fetch_task_batch(&mut self) -> Result<()> {
         let end = cmp::min(self.requested_task_titles.len(), 50);
         let next_batch_titles = self.requested_task_titles.drain(..end).collect::<Vec<_>>();
-        let mut request = Url::parse("http://rosettacode.org/mw/api.php").unwrap();
-        request
-            .query_pairs_mut()
-            .append_pair("action", "query")
-            .append_pair("format", "json")
-            .append_pair("prop", "revisions")
-            .append_pair("rvprop", "content")
-            .append_pair("titles", &next_batch_titles.join("|"));
-
-        let response: Response = self.client.get(request.as_str()).send()?.json()?;
-
-        let mut remote_tasks = remote::parse_tasks(&response);
-        remote_tasks.sort_by_key(|task| task.title());
+
+        let fetched = match self.revision_cache {
+            Some(cache) => self.fetch_pages_cached(cache, &next_batch_titles),
+            None => {
+                let request = pages_request(&next_batch_titles, "content");
+                client::get_json(self.client, &request)
+                    .map(|response: Response| remote::parse_tasks(&response))
+            }
+        };
+
+        let mut remote_tasks = match fetched {
+            Ok(remote_tasks) => remote_tasks,
+            Err(err) => {
+                let kind = match err {
+                    Error::Http(_) => FetchErrorKind::Network,
+                    Error::Json(_) | Error::Parse(_) => FetchErrorKind::Parse,
+                    _ => FetchErrorKind::Other,
+                };
+
+                return Err(FetchError {
+                    titles: next_batch_titles,
+                    kind,
+                    message: err.to_string(),
+                }.into());
+            }
+        };
+
+        let sort_order = self.sort_order;
+        remote_tasks.sort_by(|a, b| sort_order.compare(&a.title(), &b.title()));
+
         self.fetched_remote_tasks = remote_tasks.into_iter().collect();
 
         Ok(())
     }
+
+    /// Fetches the pages of a batch through the revision cache. Only the IDs of the latest
+    /// revisions are requested for the pages that are cached at their latest revision, and the
+    /// other pages are downloaded and stored in the cache.
+    fn fetch_pages_cached(
+        &self,
+        cache: &RevisionCache,
+        titles: &[String],
+    ) -> Result<Vec<RemoteTask>> {
+        let client = self.client;
+        let revisions =
+            remote::latest_revisions(titles, |request| client::get_json(client, request))?;
+
+        let mut remote_tasks = vec![];
+        let mut changed_titles = vec![];
+
+        for (title, &revid) in &revisions {
+            match cache.get(title, revid) {
+                Some(content) => remote_tasks.push(remote::parse_page(title, &content)),
+                None => changed_titles.push(title.clone()),
+            }
+        }
+
+        if !changed_titles.is_empty() {
+            let request = pages_request(&changed_titles, "ids|content");
+            let response: Response = client::get_json(client, &request)?;
+
+            for page in response.query.pages.values() {
+                if let Some(revision) = page.revisions.first() {
+                    cache.insert(&page.title, revision.revid, &revision.content);
+                }
+            }
+
+            remote_tasks.extend(remote::parse_tasks(&response));
+        }
+
+        Ok(remote_tasks)
+    }
+}
+
+/// Returns the request for the latest revision of some pages, with the given revision properties.
+fn pages_request(titles: &[String], rvprop: &str) -> Url {
+    let mut request = Url::parse(remote::API_URL).unwrap();
+    request
+        .query_pairs_mut()
+        .append_pair("action", "query")
+        .append_pair("format", "json")
+        .append_pair("prop", "revisions")
+        .append_pair("rvprop", rvprop)
+        .append_pair("titles", &titles.join("|"));
+    request
 }
 
 impl<'a> Iterator for TaskIterator<'a> {
@@ -277,8 +1360,22 @@ impl<'a> Iterator for TaskIterator<'a> {
             return None;
         }
 
-        if self.fetched_remote_tasks.is_empty() {
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                self.reached_deadline = true;
+                return None;
+            }
+        }
+
+        // A batch may not yield any tasks if none of its pages exist.
+        while self.fetched_remote_tasks.is_empty() {
+            if self.requested_task_titles.is_empty() {
+                self.report_progress();
+                return None;
+            }
+
             if let Err(err) = self.fetch_task_batch() {
+                self.report_progress();
                 return Some(Err(err));
             }
         }
@@ -292,8 +1389,684 @@ impl<'a> Iterator for TaskIterator<'a> {
         let task = Task {
             local: local_task,
             remote: remote_task,
+            changeset: RefCell::new(None),
         };
+        self.report_progress();
 
         Some(Ok(task))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+    use std::time::Instant;
+
+    use reqwest::Url;
+
+    use super::{
+        ControlFlow, DiffKind, Error, FetchErrorKind, MockWikiClient, RevisionCache, TaskIndex,
+    };
+    use local::{LocalTask, SourceFormats};
+    use temp_dir::TempDir;
+
+    const CATEGORY_RESPONSE: &str = r#"{
+        "batchcomplete": "",
+        "query": {
+            "categorymembers": [
+                { "pageid": 1, "ns": 0, "title": "100 doors" },
+                { "pageid": 2, "ns": 0, "title": "A+B" }
+            ]
+        }
+    }"#;
+
+    const REVISIONS_RESPONSE: &str = r#"{
+        "batchcomplete": "",
+        "query": {
+            "pages": {
+                "1": {
+                    "pageid": 1,
+                    "title": "100 doors",
+                    "revisions": [
+                        { "*": "=={{header|Rust}}==\n<lang rust>fn main() {}</lang>\n" }
+                    ]
+                },
+                "2": {
+                    "pageid": 2,
+                    "title": "A+B",
+                    "revisions": [
+                        { "*": "=={{header|Python}}==\n<lang python>pass</lang>\n" }
+                    ]
+                }
+            }
+        }
+    }"#;
+
+    /// Creates a local task whose crate lives in the given directory.
+    fn local_task(title: &str, crate_dir: &str) -> LocalTask {
+        let crate_dir = Path::new(crate_dir);
+
+        LocalTask {
+            package_name: crate_dir
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+            manifest_path: crate_dir.join("Cargo.toml"),
+            source: vec![crate_dir.join("src/main.rs")].into_iter().collect(),
+            formats: SourceFormats::default(),
+            dependencies: HashSet::new(),
+            url: Url::parse("http://rosettacode.org/wiki/").unwrap(),
+            title: title.to_owned(),
+        }
+    }
+
+    fn mock_client() -> MockWikiClient {
+        MockWikiClient::new()
+            .with_response(&[("list", "categorymembers")], CATEGORY_RESPONSE)
+            .with_response(&[("prop", "revisions")], REVISIONS_RESPONSE)
+    }
+
+    #[test]
+    fn fetch_all_tasks() {
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(mock_client())).unwrap();
+
+        let tasks = index
+            .fetch_all_tasks()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(tasks.len(), 2);
+
+        assert_eq!(tasks[0].title(), "100 doors");
+        assert_eq!(tasks[0].remote_code(), Some(String::from("fn main() {}")));
+        assert!(tasks[0].is_remote_only());
+        assert!(tasks[0].has_rust_section());
+        assert!(!tasks[0].is_unparseable());
+        assert_eq!(tasks[0].summary(), "100 doors [local ✗ remote ✓]");
+        assert_eq!(tasks[0].diff_ratio(), None);
+        assert_eq!(tasks[0].changeset(), None);
+
+        assert_eq!(tasks[1].title(), "A+B");
+        assert_eq!(tasks[1].remote_code(), None);
+        assert!(tasks[1].is_unimplemented());
+        assert!(!tasks[1].has_rust_section());
+        assert_eq!(tasks[1].summary(), "A+B [local ✗ remote ✗]");
+    }
+
+    #[test]
+    fn fetch_with_progress() {
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(mock_client())).unwrap();
+
+        let mut progress = vec![];
+        let tasks = index
+            .fetch_all_tasks_with_progress(|done, total| progress.push((done, total)))
+            .count();
+
+        assert_eq!(tasks, 2);
+        assert_eq!(progress, vec![(1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn revision_cache() {
+        let cache_dir = TempDir::new("rosetta-meta-revision-cache");
+
+        let revision_ids = r#"{
+            "query": {
+                "pages": {
+                    "1": { "title": "100 doors", "revisions": [{ "revid": 42 }] },
+                    "2": { "title": "A+B", "revisions": [{ "revid": 7 }] }
+                }
+            }
+        }"#;
+        let fetch = |client: Rc<MockWikiClient>| {
+            let mut index =
+                TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client)).unwrap();
+            index.revision_cache = Some(RevisionCache::new(cache_dir.path()));
+
+            let tasks = index
+                .fetch_all_tasks()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            (tasks, index.revision_cache_stats().unwrap())
+        };
+
+        let client = Rc::new(
+            MockWikiClient::new()
+                .with_response(&[("list", "categorymembers")], CATEGORY_RESPONSE)
+                .with_response(&[("rvprop", "ids")], revision_ids)
+                .with_response(
+                    &[("rvprop", "ids|content")],
+                    r#"{
+                        "query": {
+                            "pages": {
+                                "1": {
+                                    "title": "100 doors",
+                                    "revisions": [{
+                                        "revid": 42,
+                                        "*": "=={{header|Rust}}==\n<lang rust>fn main() {}</lang>\n"
+                                    }]
+                                },
+                                "2": {
+                                    "title": "A+B",
+                                    "revisions": [{ "revid": 7, "*": "=={{header|Python}}==\n" }]
+                                }
+                            }
+                        }
+                    }"#,
+                ),
+        );
+        let (tasks, stats) = fetch(client);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!((stats.misses, stats.revalidated), (2, 0));
+
+        // The revisions didn't change, so the pages are not downloaded again.
+        let client = Rc::new(
+            MockWikiClient::new()
+                .with_response(&[("list", "categorymembers")], CATEGORY_RESPONSE)
+                .with_response(&[("rvprop", "ids")], revision_ids),
+        );
+        let (tasks, stats) = fetch(client.clone());
+        assert_eq!(tasks[0].remote_code(), Some(String::from("fn main() {}")));
+        assert_eq!(tasks[1].remote_code(), None);
+        assert_eq!((stats.misses, stats.revalidated), (0, 2));
+        assert!(client.requests().iter().all(|request| !request
+            .query_pairs()
+            .any(|(key, value)| key == "rvprop" && value == "ids|content")));
+    }
+
+    #[test]
+    fn line_changes() {
+        // The crate doesn't exist, so the local code is empty.
+        let local_tasks = vec![local_task("100 doors", "/rosetta/tasks/100-doors")];
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), local_tasks, Box::new(mock_client()))
+                .unwrap();
+
+        let tasks = index
+            .fetch_all_tasks()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(tasks[0].line_changes(), Some((0, 1)));
+        assert_eq!(tasks[1].line_changes(), None);
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(
+            super::checksum("fn main() {}\n"),
+            "536e506bb90914c243a12b397b9a998f85ae2cbd9ba02dfd03a9e155ca5ca0f4"
+        );
+        assert_eq!(
+            super::checksum(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn all_titles() {
+        let client = Rc::new(mock_client());
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client.clone())).unwrap();
+        let requests = client.requests().len();
+
+        assert_eq!(
+            index.all_titles().collect::<Vec<_>>(),
+            vec!["100 doors", "A+B"]
+        );
+        assert_eq!(client.requests().len(), requests);
+    }
+
+    #[test]
+    fn omitted_tasks() {
+        let client = Rc::new(mock_client());
+        let mut index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client.clone())).unwrap();
+        index.omitted_titles.insert(String::from("A+B"));
+
+        assert!(index.is_omitted("A+B"));
+        assert_eq!(index.all_titles().collect::<Vec<_>>(), vec!["100 doors"]);
+
+        index.fetch_all_tasks().next().unwrap().unwrap();
+        let request = client.requests().pop().unwrap();
+        assert!(request
+            .query_pairs()
+            .any(|(key, value)| key == "titles" && value == "100 doors"));
+
+        // Explicitly requested tasks are fetched even if they are omitted.
+        index
+            .fetch_tasks(&[String::from("A+B")])
+            .next()
+            .unwrap()
+            .unwrap();
+        let request = client.requests().pop().unwrap();
+        assert!(request
+            .query_pairs()
+            .any(|(key, value)| key == "titles" && value == "A+B"));
+    }
+
+    #[test]
+    fn aliases() {
+        let client = Rc::new(mock_client());
+        let mut index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client.clone())).unwrap();
+        index.aliases.insert(String::from("doors"), String::from("100 doors"));
+
+        assert_eq!(index.resolve_alias("doors"), "100 doors");
+        assert_eq!(index.resolve_alias("A+B"), "A+B");
+
+        let task = index
+            .fetch_tasks(&[String::from("doors")])
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.title(), "100 doors");
+    }
+
+    #[test]
+    fn categories() {
+        let client = mock_client().with_response(
+            &[("prop", "categories")],
+            r#"{
+                "batchcomplete": "",
+                "query": {
+                    "pages": {
+                        "1": {
+                            "pageid": 1,
+                            "title": "100 doors",
+                            "categories": [
+                                { "ns": 14, "title": "Category:Iteration" },
+                                { "ns": 14, "title": "Category:Programming Tasks" }
+                            ]
+                        },
+                        "2": {
+                            "pageid": 2,
+                            "title": "A+B",
+                            "categories": [
+                                { "ns": 14, "title": "Category:Programming Tasks" }
+                            ]
+                        }
+                    }
+                }
+            }"#,
+        );
+
+        let index = TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client)).unwrap();
+        let categories = index.categories().unwrap();
+
+        assert_eq!(
+            categories.keys().collect::<Vec<_>>(),
+            vec!["Iteration", "Uncategorized"]
+        );
+        assert_eq!(categories["Iteration"], vec!["100 doors"]);
+        assert_eq!(categories["Uncategorized"], vec!["A+B"]);
+    }
+
+    #[test]
+    fn fetch_status() {
+        let client = Rc::new(mock_client().with_response(
+            &[("prop", "categories")],
+            r#"{
+                "query": {
+                    "pages": {
+                        "1": {
+                            "title": "100 doors",
+                            "categories": [
+                                { "ns": 14, "title": "Category:Programming Tasks" },
+                                { "ns": 14, "title": "Category:Rust" }
+                            ]
+                        },
+                        "2": {
+                            "title": "A+B",
+                            "categories": [
+                                { "ns": 14, "title": "Category:Programming Tasks" }
+                            ]
+                        }
+                    }
+                }
+            }"#,
+        ));
+        let index = TaskIndex::from_local_tasks(
+            PathBuf::from("/rosetta"),
+            vec![local_task("A+B", "/rosetta/tasks/a-plus-b")],
+            Box::new(client.clone()),
+        ).unwrap();
+
+        let statuses = index.fetch_status(&[]).unwrap();
+        assert_eq!(
+            statuses
+                .iter()
+                .map(|status| status.summary())
+                .collect::<Vec<_>>(),
+            vec!["100 doors [local ✗ remote ✓]", "A+B [local ✓ remote ✗]"]
+        );
+
+        // The code of the tasks is never downloaded.
+        assert!(client.requests().iter().all(|request| !request
+            .query_pairs()
+            .any(|(key, value)| key == "rvprop" && value == "content")));
+    }
+
+    #[test]
+    fn latest_revisions() {
+        let client = MockWikiClient::new()
+            .with_response(&[("list", "categorymembers")], CATEGORY_RESPONSE)
+            .with_response(
+                &[("prop", "revisions"), ("rvprop", "ids")],
+                r#"{
+                    "query": {
+                        "pages": {
+                            "1": { "title": "100 doors", "revisions": [{ "revid": 42 }] },
+                            "-1": { "title": "Not a task", "missing": "" }
+                        }
+                    }
+                }"#,
+            );
+        let index = TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client)).unwrap();
+
+        let revisions = index.latest_revisions(&[]).unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions["100 doors"], 42);
+    }
+
+    #[test]
+    fn with_remote_code() {
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(mock_client())).unwrap();
+        let task = index.fetch_all_tasks().next().unwrap().unwrap();
+
+        let old_code = Some(String::from("fn old() {}"));
+        let old = task.clone().with_remote_code(old_code.clone());
+        assert_eq!(old.remote_code(), old_code);
+        assert!(old.is_remote_only());
+
+        let removed = task.with_remote_code(None);
+        assert_eq!(removed.remote_code(), None);
+        assert!(removed.is_unimplemented());
+    }
+
+    #[test]
+    fn naming_mismatches() {
+        let mut index = TaskIndex::from_local_tasks(
+            PathBuf::from("/rosetta"),
+            vec![
+                local_task("100 doors", "/rosetta/tasks/100-doors"),
+                local_task("A+B", "/rosetta/tasks/aplusb"),
+            ],
+            Box::new(mock_client()),
+        ).unwrap();
+
+        let mismatches = index.naming_mismatches();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].title, "A+B");
+        assert_eq!(mismatches[0].dir, Path::new("tasks/aplusb"));
+        assert_eq!(mismatches[0].suggested_dir, Path::new("tasks/a-plus-b"));
+
+        index.omitted_titles.insert(String::from("A+B"));
+        assert!(index.naming_mismatches().is_empty());
+    }
+
+    #[test]
+    fn subpage_task() {
+        let title = "Sorting algorithms/Quicksort";
+        let client = Rc::new(
+            MockWikiClient::new()
+                .with_response(
+                    &[("list", "categorymembers")],
+                    r#"{ "query": { "categorymembers": [
+                        { "ns": 0, "title": "Sorting algorithms/Quicksort" }
+                    ] } }"#,
+                )
+                .with_response(
+                    &[("prop", "revisions"), ("titles", title)],
+                    r#"{ "query": { "pages": { "1": {
+                        "pageid": 1,
+                        "title": "Sorting algorithms/Quicksort",
+                        "revisions": [
+                            { "*": "=={{header|Rust}}==\n<lang rust>fn main() {}</lang>\n" }
+                        ]
+                    } } } }"#,
+                ),
+        );
+        let local_task = local_task(title, "/rosetta/tasks/sorting-algorithms/quicksort");
+        let index = TaskIndex::from_local_tasks(
+            PathBuf::from("/rosetta"),
+            vec![local_task],
+            Box::new(client.clone()),
+        ).unwrap();
+        assert!(index.naming_mismatches().is_empty());
+
+        let tasks = index
+            .fetch_tasks(&[String::from(title)])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title(), title);
+        assert!(tasks[0].local.is_some());
+
+        // The slash separates a subpage on the wiki, so it is kept as is.
+        assert_eq!(
+            tasks[0].url().as_str(),
+            "http://rosettacode.org/wiki/Sorting_algorithms/Quicksort"
+        );
+        assert!(tasks[0]
+            .edit_url()
+            .query_pairs()
+            .any(|(key, value)| key == "title" && value == title));
+    }
+
+    #[test]
+    fn modules() {
+        let index = TaskIndex::from_local_tasks(
+            PathBuf::from("/rosetta"),
+            vec![
+                local_task("100 doors", "/rosetta/tasks/puzzles/100-doors"),
+                local_task("A+B", "/rosetta/tasks/a-plus-b"),
+            ],
+            Box::new(mock_client()),
+        ).unwrap();
+
+        let modules = index.modules();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules["puzzles"], vec!["100 doors"]);
+    }
+
+    #[test]
+    fn is_implemented_locally() {
+        let index = TaskIndex::from_local_tasks(
+            PathBuf::new(),
+            vec![local_task("100 doors", "/rosetta/tasks/100-doors")],
+            Box::new(mock_client()),
+        ).unwrap();
+
+        assert!(index.is_implemented_locally("100 doors"));
+        assert!(!index.is_implemented_locally("A+B"));
+    }
+
+    #[test]
+    fn local_paths() {
+        let index = TaskIndex::from_local_tasks(
+            PathBuf::new(),
+            vec![local_task("100 doors", "/rosetta/tasks/100-doors")],
+            Box::new(mock_client()),
+        ).unwrap();
+        let tasks = index
+            .fetch_all_tasks()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            tasks[0].manifest_dir(),
+            Some(Path::new("/rosetta/tasks/100-doors"))
+        );
+        assert_eq!(
+            tasks[0].local_source_path(),
+            Some(Path::new("/rosetta/tasks/100-doors/src/main.rs"))
+        );
+
+        assert_eq!(tasks[1].manifest_dir(), None);
+        assert_eq!(tasks[1].local_source_path(), None);
+    }
+
+    #[test]
+    fn fetch_selected_tasks() {
+        let client = Rc::new(mock_client());
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client.clone())).unwrap();
+
+        index
+            .fetch_tasks(&[String::from("A+B"), String::from("Not a task")])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        // Only titles that are known to the index should be requested from the wiki.
+        let request = client.requests().pop().unwrap();
+        assert!(request
+            .query_pairs()
+            .any(|(key, value)| key == "titles" && value == "A+B"));
+    }
+
+    #[test]
+    fn fetch_lowercase_title() {
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(mock_client())).unwrap();
+
+        let tasks = index
+            .fetch_tasks(&[String::from("a+B")])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title(), "A+B");
+    }
+
+    #[test]
+    fn fetch_error() {
+        let client = MockWikiClient::new()
+            .with_response(&[("list", "categorymembers")], CATEGORY_RESPONSE)
+            .with_response(&[("prop", "revisions")], "not json");
+        let index = TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client)).unwrap();
+
+        let mut tasks = index.fetch_all_tasks();
+        let err = tasks.next().unwrap().unwrap_err();
+        let err = match err {
+            Error::Fetch(err) => err,
+            err => panic!("expected a fetch error, got {}", err),
+        };
+
+        assert_eq!(err.titles, vec!["100 doors", "A+B"]);
+        assert_eq!(err.kind, FetchErrorKind::Parse);
+        assert!(tasks.next().is_none());
+    }
+
+    #[test]
+    fn deadline() {
+        let client = Rc::new(mock_client());
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client.clone())).unwrap();
+        let requests = client.requests().len();
+
+        let mut tasks = index.fetch_all_tasks().with_deadline(Instant::now());
+
+        assert!(tasks.next().is_none());
+        assert!(tasks.reached_deadline());
+        assert_eq!(tasks.remaining(), 2);
+        assert_eq!(client.requests().len(), requests);
+    }
+
+    #[test]
+    fn for_each_task() {
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(mock_client())).unwrap();
+
+        let mut statuses = vec![];
+        index
+            .for_each_task(
+                |task| task.is_unimplemented(),
+                |status| {
+                    statuses.push(status);
+                    ControlFlow::Continue
+                },
+            )
+            .unwrap();
+
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].title, "A+B");
+        assert!(!statuses[0].implemented_locally);
+        assert!(!statuses[0].implemented_remotely);
+        assert_eq!(statuses[0].diff_kind, DiffKind::Missing);
+
+        let mut titles = vec![];
+        index
+            .for_each_task(
+                |_| true,
+                |status| {
+                    titles.push(status.title);
+                    ControlFlow::Break
+                },
+            )
+            .unwrap();
+
+        assert_eq!(titles, vec!["100 doors"]);
+    }
+
+    #[test]
+    fn fetch_namespaced_page() {
+        let client = Rc::new(mock_client());
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::new(), vec![], Box::new(client.clone())).unwrap();
+
+        assert!(!index.all_titles().any(|title| title.contains(':')));
+
+        index
+            .fetch_tasks(&[String::from("Category:Sorting Algorithms")])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let request = client.requests().pop().unwrap();
+        assert!(request
+            .query_pairs()
+            .any(|(key, value)| key == "titles" && value == "Category:Sorting Algorithms"));
+    }
+
+    #[test]
+    fn title_for_path() {
+        let local_tasks = vec![
+            local_task("24 game", "/rosetta/tasks/24-game"),
+            local_task("24 game/Solve", "/rosetta/tasks/24-game/solve"),
+        ];
+
+        let client = MockWikiClient::new().with_response(
+            &[("list", "categorymembers")],
+            r#"{
+                "query": {
+                    "categorymembers": [
+                        { "pageid": 1, "ns": 0, "title": "24 game" },
+                        { "pageid": 2, "ns": 0, "title": "24 game/Solve" }
+                    ]
+                }
+            }"#,
+        );
+
+        let index =
+            TaskIndex::from_local_tasks(PathBuf::from("/rosetta"), local_tasks, Box::new(client))
+                .unwrap();
+
+        assert_eq!(
+            index.title_for_path(Path::new("/rosetta/tasks/24-game/src/main.rs")),
+            Some(String::from("24 game"))
+        );
+        assert_eq!(
+            index.title_for_path(Path::new("/rosetta/tasks/24-game/solve/src/main.rs")),
+            Some(String::from("24 game/Solve"))
+        );
+        assert_eq!(
+            index.title_for_path(Path::new("tasks/24-game/solve/src/lib.rs")),
+            Some(String::from("24 game/Solve"))
+        );
+        assert_eq!(index.title_for_path(Path::new("/elsewhere/main.rs")), None);
+    }
+}
@@ -1,18 +1,304 @@
 //! Utilities for interacting with tasks implemented in the rust-rosetta repository.
 
-use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use cargo_metadata;
-use failure::{self, Error, ResultExt};
+use glob::{MatchOptions, Pattern};
+use pathdiff;
+use regex::Regex;
 use reqwest::Url;
+use serde_json;
 use toml::Value;
-use walkdir::WalkDir;
+use walkdir::{DirEntry, WalkDir};
 
+use error::{Error, IndexError};
 use remote;
+use sort::fold_diacritic;
 use TASK_URL_RE;
 
+lazy_static! {
+    /// Matches `extern crate` declarations, capturing the name of the crate.
+    static ref EXTERN_CRATE_RE: Regex =
+        Regex::new(r"(?m)^\s*(?:pub\s+)?extern\s+crate\s+([A-Za-z0-9_]+)").unwrap();
+
+    /// Matches the declaration of a `main` function.
+    static ref MAIN_FN_RE: Regex = Regex::new(r"(?m)^\s*(?:pub\s+)?fn\s+main\s*\(").unwrap();
+
+    /// Matches a crate attribute declaring the crate to be a library.
+    static ref LIB_CRATE_TYPE_RE: Regex =
+        Regex::new(r#"#!\[\s*crate_type\s*=\s*"(?:lib|rlib|dylib)"\s*\]"#).unwrap();
+
+    /// Matches a `// TODO` or `// FIXME` comment, capturing the marker.
+    static ref TODO_COMMENT_RE: Regex = Regex::new(r"//+!?\s*(TODO|FIXME)\b").unwrap();
+
+    /// Matches an invocation of the `unimplemented!` or `todo!` macros.
+    static ref TODO_MACRO_RE: Regex = Regex::new(r"\b(?:unimplemented|todo)!\s*[(\[{]").unwrap();
+}
+
+/// Crates that are distributed with the compiler, and so never need to be declared.
+const BUILTIN_CRATES: &[&str] = &["alloc", "core", "proc_macro", "std", "test"];
+
+/// How the code of a task is extracted from one of its source files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceFormat {
+    /// The whole file is Rust code.
+    Rust,
+
+    /// The file is a Markdown document, and the code is in its fenced `rust` code blocks.
+    Markdown,
+}
+
+impl SourceFormat {
+    /// Extracts the code from the contents of a source file.
+    pub fn extract(self, text: &str) -> String {
+        match self {
+            SourceFormat::Rust => text.to_owned(),
+            SourceFormat::Markdown => markdown_code(text),
+        }
+    }
+}
+
+/// Returns the contents of the fenced code blocks of a Markdown document that are marked as
+/// `rust`, such as `` ```rust `` or `` ```rust,ignore ``. The blocks are separated by a blank line.
+fn markdown_code(text: &str) -> String {
+    let mut blocks = vec![];
+    let mut block: Option<String> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        match block.take() {
+            Some(mut code) => {
+                if trimmed.starts_with("```") && trimmed.trim_left_matches('`').is_empty() {
+                    blocks.push(code);
+                } else {
+                    code.push_str(line);
+                    code.push('\n');
+                    block = Some(code);
+                }
+            }
+            None if trimmed.starts_with("```") => {
+                let info = trimmed.trim_left_matches('`');
+                let language = info.split(|c: char| c == ',' || c.is_whitespace()).next();
+                if language == Some("rust") {
+                    block = Some(String::new());
+                }
+            }
+            None => (),
+        }
+    }
+
+    blocks.join("\n")
+}
+
+/// The file extensions that contain the code of a task, and how the code is extracted from each.
+///
+/// By default, only `.rs` files are read, as Rust code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SourceFormats {
+    by_extension: BTreeMap<String, SourceFormat>,
+}
+
+impl SourceFormats {
+    /// Creates the default formats, which only include `.rs` files.
+    pub fn new() -> SourceFormats {
+        Default::default()
+    }
+
+    /// Reads files with the given extension (without the leading dot) in the given format,
+    /// replacing the format that was previously used for the extension.
+    pub fn with_extension<S: Into<String>>(
+        mut self,
+        extension: S,
+        format: SourceFormat,
+    ) -> SourceFormats {
+        self.by_extension.insert(extension.into(), format);
+        self
+    }
+
+    /// Returns the format of a file, or `None` if the file does not contain code.
+    pub fn format_of(&self, path: &Path) -> Option<SourceFormat> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.by_extension.get(ext))
+            .cloned()
+    }
+}
+
+impl Default for SourceFormats {
+    fn default() -> SourceFormats {
+        SourceFormats {
+            by_extension: Some((String::from("rs"), SourceFormat::Rust))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// The name of the file in the workspace root that lists the patterns of an `IgnorePatterns`.
+pub const IGNORE_FILE: &str = ".rosettaignore";
+
+/// Gitignore-style patterns of files and directories that are skipped when the workspace is
+/// scanned for tasks, such as generated or vendored code.
+///
+/// Each pattern is a glob. Patterns without a slash match the name of a file or directory at any
+/// depth, and other patterns match paths relative to the workspace root. A trailing slash only
+/// matches directories, and a leading `!` includes paths that an earlier pattern ignored.
+/// Everything inside an ignored directory is ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IgnorePatterns {
+    rules: Vec<IgnoreRule>,
+}
+
+/// A single pattern of `IgnorePatterns`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct IgnoreRule {
+    pattern: Pattern,
+    anchored: bool,
+    dir_only: bool,
+    negated: bool,
+}
+
+impl IgnorePatterns {
+    /// Creates an empty set of patterns, which doesn't ignore anything.
+    pub fn new() -> IgnorePatterns {
+        Default::default()
+    }
+
+    /// Parses patterns, one per line. Blank lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<IgnorePatterns, Error> {
+        let mut patterns = IgnorePatterns::new();
+
+        for line in text.lines().map(str::trim) {
+            if !line.is_empty() && !line.starts_with('#') {
+                patterns.add(line)?;
+            }
+        }
+
+        Ok(patterns)
+    }
+
+    /// Reads the patterns in the `IGNORE_FILE` of a workspace. If there is no such file, nothing
+    /// is ignored.
+    pub fn read(workspace_root: &Path) -> Result<IgnorePatterns, Error> {
+        let path = workspace_root.join(IGNORE_FILE);
+
+        let text = fs::read_to_string(&path);
+        match text {
+            Ok(text) => IgnorePatterns::parse(&text),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(IgnorePatterns::new()),
+            Err(err) => Err(IndexError::UnreadableFile {
+                path,
+                cause: err.to_string(),
+            }.into()),
+        }
+    }
+
+    /// Adds a pattern, which takes precedence over the existing ones.
+    pub fn add(&mut self, pattern: &str) -> Result<(), Error> {
+        let negated = pattern.starts_with('!');
+        let pattern = pattern.trim_left_matches('!');
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_right_matches('/');
+        let anchored = pattern.contains('/');
+        let glob = pattern.trim_left_matches('/');
+
+        let pattern = Pattern::new(glob).map_err(|err| {
+            Error::Parse(format!("invalid ignore pattern '{}': {}", pattern, err))
+        })?;
+
+        self.rules.push(IgnoreRule {
+            pattern,
+            anchored,
+            dir_only,
+            negated,
+        });
+        Ok(())
+    }
+
+    /// True if there are no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// True if a path relative to the workspace root is ignored, either by itself or because a
+    /// directory that contains it is ignored. `is_dir` tells whether the path is a directory.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+
+        let components = path.components().collect::<Vec<_>>();
+
+        (1..components.len() + 1).any(|len| {
+            let prefix = components[..len]
+                .iter()
+                .map(|component| component.as_os_str())
+                .collect::<PathBuf>();
+            self.matches(&prefix, is_dir || len < components.len())
+        })
+    }
+
+    /// True if the last pattern that matches a path ignores it.
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+
+        let mut ignored = false;
+
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+
+            let matched = if rule.anchored {
+                rule.pattern.matches_path_with(path, &options)
+            } else {
+                path.file_name().map_or(false, |name| {
+                    rule.pattern.matches_with(&name.to_string_lossy(), &options)
+                })
+            };
+
+            if matched {
+                ignored = !rule.negated;
+            }
+        }
+
+        ignored
+    }
+}
+
+/// Which files of the workspace are read when it is scanned for tasks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ScanOptions {
+    /// The files that contain the code of tasks, and how the code is extracted from them.
+    pub formats: SourceFormats,
+
+    /// The files and directories that are skipped.
+    pub ignore: IgnorePatterns,
+}
+
+impl ScanOptions {
+    /// Creates the default options for a workspace, which read `.rs` files and skip the paths in
+    /// the `IGNORE_FILE` of the workspace, if it has one.
+    pub fn for_workspace(workspace_root: &Path) -> Result<ScanOptions, Error> {
+        Ok(ScanOptions {
+            formats: SourceFormats::default(),
+            ignore: IgnorePatterns::read(workspace_root)?,
+        })
+    }
+}
+
 /// A local (in repository) implementation of a Rosetta Code task.
 #[derive(Debug, Clone)]
 pub struct LocalTask {
@@ -25,6 +311,13 @@ pub struct LocalTask {
     /// A set of filenames containing the Rust source code of the tasks.
     pub source: HashSet<PathBuf>,
 
+    /// How the code is extracted from each source file.
+    pub formats: SourceFormats,
+
+    /// The names of the crates that the task depends on, as they would be written in an
+    /// `extern crate` declaration.
+    pub dependencies: HashSet<String>,
+
     /// The URL of the task on the Rosetta Code wiki.
     ///
     /// This is parsed from the crate metadata, so it may not point to an actual Rosetta Code URL.
@@ -36,13 +329,99 @@ pub struct LocalTask {
     pub title: String,
 }
 
+impl LocalTask {
+    /// Reads the code of the task.
+    ///
+    /// The code of each source file is extracted according to `formats`, and the files are
+    /// concatenated in the order of their paths. Files that can't be read are skipped with a
+    /// warning.
+    pub fn code(&self) -> String {
+        let mut sources = self.source.iter().collect::<Vec<_>>();
+        sources.sort();
+
+        // FIXME: Too simple for the multiple source file case.
+        let mut code = String::new();
+
+        for source in sources {
+            let format = self.formats.format_of(source).unwrap_or(SourceFormat::Rust);
+            match read_source(source) {
+                Ok(source_code) => code.push_str(&format.extract(&source_code)),
+                Err(err) => warn!("could not read {}: {}", source.display(), err),
+            }
+        }
+
+        code
+    }
+
+    /// Finds the markers of incomplete work in the source files of the task, sorted by path and
+    /// line. See `todo_kind`. Files that can't be read are skipped with a warning.
+    pub fn todo_markers(&self) -> Vec<TodoMarker> {
+        let mut sources = self.source.iter().collect::<Vec<_>>();
+        sources.sort();
+
+        let mut markers = vec![];
+
+        for source in sources {
+            let code = match read_source(source) {
+                Ok(code) => code,
+                Err(err) => {
+                    warn!("could not read {}: {}", source.display(), err);
+                    continue;
+                }
+            };
+
+            for (index, line) in code.lines().enumerate() {
+                if let Some(kind) = todo_kind(line) {
+                    markers.push(TodoMarker {
+                        path: source.clone(),
+                        line: index + 1,
+                        kind,
+                        text: line.trim().to_owned(),
+                    });
+                }
+            }
+        }
+
+        markers
+    }
+
+    /// Returns the time that a source file of the task was last modified, or `None` if none of
+    /// the modification times can be read.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.source
+            .iter()
+            .filter_map(|source| fs::metadata(source).and_then(|m| m.modified()).ok())
+            .max()
+    }
+}
+
 /// Given a path to the root `Cargo.toml`, returns a list of tasks implemented in the rust-rosetta
-/// repository.
+/// repository. The workspace is scanned with `ScanOptions::for_workspace`.
 pub fn parse_tasks<P>(manifest_path: P) -> Result<Vec<LocalTask>, Error>
 where
     P: AsRef<Path>,
 {
-    let metadata = cargo_metadata::metadata(Some(&manifest_path.as_ref())).unwrap();
+    let manifest_path = manifest_path.as_ref();
+    let options = ScanOptions::for_workspace(manifest_path.parent().unwrap())?;
+    parse_tasks_with(manifest_path, &options)
+}
+
+/// Like `parse_tasks`, but the workspace is scanned with the given options. Tasks whose crates
+/// are ignored are skipped.
+pub fn parse_tasks_with<P>(manifest_path: P, options: &ScanOptions) -> Result<Vec<LocalTask>, Error>
+where
+    P: AsRef<Path>,
+{
+    let manifest_path = manifest_path.as_ref();
+
+    if !manifest_path.is_file() {
+        return Err(IndexError::MissingManifest(manifest_path.to_owned()).into());
+    }
+
+    let workspace_root = manifest_path.parent().unwrap();
+
+    let metadata = cargo_metadata::metadata(Some(&manifest_path))
+        .map_err(|err| IndexError::Metadata(err.to_string()))?;
     let packages = &metadata.packages;
 
     let mut tasks = vec![];
@@ -64,15 +443,26 @@ where
         }
 
         let manifest_path = Path::new(&package.manifest_path);
+        let crate_dir = manifest_path.parent().unwrap();
 
-        let rosetta_url = parse_rosetta_url(manifest_path).context(format!(
-            "could not parse rosetta code URL from {}",
-            manifest_path.display()
-        ))?;
+        if let Ok(relative_dir) = crate_dir.strip_prefix(workspace_root) {
+            if options.ignore.is_ignored(relative_dir, true) {
+                debug!("ignoring the crate in {}", crate_dir.display());
+                continue;
+            }
+        }
+
+        let rosetta_url = parse_rosetta_url(manifest_path).map_err(|err| {
+            Error::Parse(format!(
+                "could not parse rosetta code URL from {}: {}",
+                manifest_path.display(),
+                err
+            ))
+        })?;
 
         let title = {
             let caps = TASK_URL_RE.captures(rosetta_url.as_str()).ok_or_else(|| {
-                failure::err_msg(format!(
+                Error::Parse(format!(
                     "task URL does not match rosetta code regex: {}",
                     rosetta_url
                 ))
@@ -80,10 +470,27 @@ where
             remote::decode_title(&caps[1])
         };
 
+        // Dependencies may declare a library name that is different from the package name, so look
+        // up the library target of each dependency.
+        let dependencies = package
+            .dependencies
+            .iter()
+            .map(|dependency| {
+                packages
+                    .iter()
+                    .find(|p| p.name == dependency.name)
+                    .and_then(|p| p.targets.iter().find(|t| is_library(&t.kind)))
+                    .map(|t| t.name.replace("-", "_"))
+                    .unwrap_or_else(|| dependency.name.replace("-", "_"))
+            })
+            .collect();
+
         tasks.push(LocalTask {
             package_name: member.name.clone(),
             manifest_path: manifest_path.to_owned(),
-            source: find_sources(manifest_path.parent().unwrap())?,
+            source: find_sources(crate_dir, workspace_root, options)?,
+            formats: options.formats.clone(),
+            dependencies,
             url: rosetta_url,
             title,
         });
@@ -92,12 +499,496 @@ where
     Ok(tasks)
 }
 
+/// Reads the `[package.metadata.rosettacode]` table of the root `Cargo.toml`, if it exists.
+fn rosettacode_metadata(manifest_path: &Path) -> Result<Option<Value>, Error> {
+    let manifest: Value = fs::read_to_string(manifest_path)
+        .map_err(|err| IndexError::UnreadableFile {
+            path: manifest_path.to_owned(),
+            cause: err.to_string(),
+        })?
+        .parse()?;
+
+    Ok(manifest
+        .get("package")
+        .and_then(|p| p.get("metadata"))
+        .and_then(|m| m.get("rosettacode"))
+        .cloned())
+}
+
+/// Returns the titles of the tasks that are intentionally omitted from the repository.
+///
+/// They are listed in the `omit` key of the `[package.metadata.rosettacode]` table of the root
+/// `Cargo.toml`. If the key is missing, no tasks are omitted.
+pub fn parse_omitted_titles<P>(manifest_path: P) -> Result<BTreeSet<String>, Error>
+where
+    P: AsRef<Path>,
+{
+    let metadata = rosettacode_metadata(manifest_path.as_ref())?;
+
+    let omit = match metadata.as_ref().and_then(|m| m.get("omit")) {
+        Some(omit) => omit,
+        None => return Ok(BTreeSet::new()),
+    };
+
+    omit.as_array()
+        .and_then(|titles| {
+            titles
+                .iter()
+                .map(|title| title.as_str().map(String::from))
+                .collect()
+        })
+        .ok_or_else(|| Error::Parse("expected `omit` to be a list of task titles".into()))
+}
+
+/// Returns the alternate names of tasks, mapped to the titles of the tasks.
+///
+/// They are listed in the `[package.metadata.rosettacode.aliases]` table of the root
+/// `Cargo.toml`, such as `qsort = "Sorting algorithms/Quicksort"`. If the table is missing, there
+/// are no aliases.
+pub fn parse_aliases<P>(manifest_path: P) -> Result<BTreeMap<String, String>, Error>
+where
+    P: AsRef<Path>,
+{
+    let metadata = rosettacode_metadata(manifest_path.as_ref())?;
+
+    let aliases = match metadata.as_ref().and_then(|m| m.get("aliases")) {
+        Some(aliases) => aliases,
+        None => return Ok(BTreeMap::new()),
+    };
+
+    aliases
+        .as_table()
+        .and_then(|aliases| {
+            aliases
+                .iter()
+                .map(|(alias, title)| title.as_str().map(|title| (alias.clone(), title.into())))
+                .collect()
+        })
+        .ok_or_else(|| Error::Parse("expected `aliases` to map aliases to task titles".into()))
+}
+
+/// Like `parse_tasks`, but reuses the tasks stored in the cache file at `cache_path` if no manifest
+/// or source file in the workspace has been added, removed or modified since the cache was
+/// written.
+///
+/// Otherwise, the workspace is scanned again and the cache is rewritten. Failing to write the
+/// cache is not an error, since the tasks can still be returned.
+///
+/// The workspace is scanned with the given options. A cache written with different options is not
+/// reused.
+pub fn parse_tasks_cached<P, Q>(
+    manifest_path: P,
+    cache_path: Q,
+    options: &ScanOptions,
+) -> Result<Vec<LocalTask>, Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let manifest_path = manifest_path.as_ref();
+    let cache_path = cache_path.as_ref();
+
+    if !manifest_path.is_file() {
+        return Err(IndexError::MissingManifest(manifest_path.to_owned()).into());
+    }
+
+    let fingerprint = workspace_fingerprint(manifest_path.parent().unwrap(), options)?;
+
+    if let Some(tasks) = read_cache(cache_path, fingerprint) {
+        return Ok(tasks);
+    }
+
+    let tasks = parse_tasks_with(manifest_path, options)?;
+
+    if let Err(err) = write_cache(cache_path, fingerprint, &tasks) {
+        warn!(
+            "could not write the task index cache to {}: {}",
+            cache_path.display(),
+            err
+        );
+    }
+
+    Ok(tasks)
+}
+
+/// The contents of the cache file written by `parse_tasks_cached`.
+#[derive(Debug, Serialize, Deserialize)]
+struct TaskCache {
+    /// The fingerprint of the workspace at the time that the tasks were parsed.
+    fingerprint: u64,
+
+    /// The tasks that were parsed.
+    tasks: Vec<CachedTask>,
+}
+
+/// A `LocalTask` in a form that can be serialized.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedTask {
+    package_name: String,
+    manifest_path: PathBuf,
+    source: HashSet<PathBuf>,
+    formats: SourceFormats,
+    dependencies: HashSet<String>,
+    url: String,
+    title: String,
+}
+
+/// Reads the tasks from a cache file. Returns `None` if the cache is missing, unreadable, or was
+/// written for a different fingerprint.
+fn read_cache(cache_path: &Path, fingerprint: u64) -> Option<Vec<LocalTask>> {
+    let contents = fs::read_to_string(cache_path).ok()?;
+    let cache: TaskCache = serde_json::from_str(&contents).ok()?;
+
+    if cache.fingerprint != fingerprint {
+        return None;
+    }
+
+    cache
+        .tasks
+        .into_iter()
+        .map(|task| {
+            Some(LocalTask {
+                package_name: task.package_name,
+                manifest_path: task.manifest_path,
+                source: task.source,
+                formats: task.formats,
+                dependencies: task.dependencies,
+                url: Url::parse(&task.url).ok()?,
+                title: task.title,
+            })
+        })
+        .collect()
+}
+
+/// Writes tasks to a cache file, creating its parent directory if necessary.
+fn write_cache(cache_path: &Path, fingerprint: u64, tasks: &[LocalTask]) -> Result<(), Error> {
+    let cache = TaskCache {
+        fingerprint,
+        tasks: tasks
+            .iter()
+            .map(|task| CachedTask {
+                package_name: task.package_name.clone(),
+                manifest_path: task.manifest_path.clone(),
+                source: task.source.clone(),
+                formats: task.formats.clone(),
+                dependencies: task.dependencies.clone(),
+                url: task.url.to_string(),
+                title: task.title.clone(),
+            })
+            .collect(),
+    };
+
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(cache_path, serde_json::to_string(&cache)?)?;
+    Ok(())
+}
+
+/// Computes a fingerprint of the paths and modification times of every manifest and source file
+/// in the workspace, and of the options that determine which files are source files. Hidden
+/// directories, build output and ignored files are skipped.
+///
+/// The fingerprint is only meaningful for the build of the crate that computed it, since the
+/// hashing algorithm of the standard library may change.
+fn workspace_fingerprint(root: &Path, options: &ScanOptions) -> Result<u64, Error> {
+    let mut hasher = DefaultHasher::new();
+    options.hash(&mut hasher);
+
+    let walker = WalkDir::new(root)
+        .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+        .into_iter()
+        .filter_entry(|entry| {
+            !is_hidden_or_build_output(entry) && !is_ignored_entry(entry, root, &options.ignore)
+        });
+
+    for entry in walker {
+        let entry = entry.map_err(|err| IndexError::UnreadableFile {
+            path: err.path().unwrap_or(root).to_owned(),
+            cause: err.to_string(),
+        })?;
+
+        let path = entry.path();
+        let is_source = options.formats.format_of(path).is_some();
+        let is_manifest = path.file_name().map_or(false, |name| name == "Cargo.toml");
+
+        if !is_source && !is_manifest {
+            continue;
+        }
+
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|err| IndexError::UnreadableFile {
+                path: path.to_owned(),
+                cause: err.to_string(),
+            })?;
+
+        path.hash(&mut hasher);
+        modified.hash(&mut hasher);
+    }
+
+    Ok(hasher.finish())
+}
+
+/// Reads the contents of a source file as clean text.
+///
+/// A leading UTF-8 byte order mark is removed. If the file is not valid UTF-8, a warning is
+/// printed and invalid sequences are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+pub fn read_source<P>(path: P) -> io::Result<String>
+where
+    P: AsRef<Path>,
+{
+    const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+    let path = path.as_ref();
+    let bytes = fs::read(path)?;
+    let bytes = if bytes.starts_with(BOM) {
+        &bytes[BOM.len()..]
+    } else {
+        &bytes[..]
+    };
+
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(source) => Ok(source),
+        Err(_) => {
+            warn!(
+                "{} is not valid UTF-8, invalid bytes will be replaced",
+                path.display()
+            );
+            Ok(String::from_utf8_lossy(bytes).into_owned())
+        }
+    }
+}
+
+/// Returns the names of the crates referenced by `extern crate` declarations in some code.
+///
+/// Crates that are distributed with the compiler, such as `std` or `test`, are not included.
+pub fn referenced_crates(code: &str) -> BTreeSet<String> {
+    EXTERN_CRATE_RE
+        .captures_iter(code)
+        .map(|captures| captures[1].to_owned())
+        .filter(|name| !BUILTIN_CRATES.contains(&name.as_str()))
+        .collect()
+}
+
+/// True if some code declares a `main` function.
+pub fn defines_main(code: &str) -> bool {
+    MAIN_FN_RE.is_match(code)
+}
+
+/// The kinds of markers of incomplete work in the code of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoKind {
+    /// A `// TODO` comment.
+    Todo,
+
+    /// A `// FIXME` comment.
+    Fixme,
+
+    /// An invocation of the `unimplemented!` or `todo!` macros.
+    Unimplemented,
+}
+
+/// A line in the code of a task that marks incomplete work.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TodoMarker {
+    /// The source file that contains the marker.
+    pub path: PathBuf,
+
+    /// The number of the line in the file, starting at 1.
+    pub line: usize,
+
+    /// The kind of the marker.
+    pub kind: TodoKind,
+
+    /// The line, without surrounding whitespace.
+    pub text: String,
+}
+
+/// Returns the kind of marker of incomplete work on a line of code, if there is one. Comments are
+/// preferred over macros if a line has both.
+pub fn todo_kind(line: &str) -> Option<TodoKind> {
+    if let Some(captures) = TODO_COMMENT_RE.captures(line) {
+        return Some(if &captures[1] == "TODO" {
+            TodoKind::Todo
+        } else {
+            TodoKind::Fixme
+        });
+    }
+
+    if TODO_MACRO_RE.is_match(line) {
+        Some(TodoKind::Unimplemented)
+    } else {
+        None
+    }
+}
+
+/// True if the sources of a task make up a library rather than a program.
+///
+/// A task is a library if its code declares a library `crate_type`, or if it has a `lib.rs` but
+/// no `main.rs`.
+pub fn is_library_task(sources: &HashSet<PathBuf>, code: &str) -> bool {
+    let has_file = |name: &str| {
+        sources
+            .iter()
+            .any(|path| path.file_name().map_or(false, |file| file == name))
+    };
+
+    LIB_CRATE_TYPE_RE.is_match(code) || (has_file("lib.rs") && !has_file("main.rs"))
+}
+
+/// True if a target with the given kinds can be linked with `extern crate`.
+fn is_library(kinds: &[String]) -> bool {
+    kinds
+        .iter()
+        .any(|kind| kind == "lib" || kind == "rlib" || kind == "dylib" || kind == "proc-macro")
+}
+
+/// Returns the conventional path of the crate implementing a task, relative to the repository
+/// root.
+///
+/// Each component of the title is lowercased and joined with hyphens, so `Hello world/Text` maps
+/// to `tasks/hello-world/text`. Subpages of a task become subdirectories.
+pub fn task_dir(title: &str) -> PathBuf {
+    let mut path = PathBuf::from("tasks");
+
+    for component in title.split('/') {
+        path.push(slug_component(component));
+    }
+
+    path
+}
+
+/// Converts the path of a task crate, relative to the repository root, back to the title that
+/// it reads as. This is the reverse of `task_dir`, except that capitalization and punctuation are
+/// lost, so `tasks/hello-world/text` becomes `hello world/text`.
+///
+/// Returns `None` if the path is not inside the `tasks` directory.
+pub fn dir_title(dir: &Path) -> Option<String> {
+    let components = dir.strip_prefix("tasks").ok()?;
+
+    let title = components
+        .iter()
+        .map(|component| {
+            component
+                .to_string_lossy()
+                .split('-')
+                .map(|word| if word == "plus" { "+" } else { word })
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace(" + ", "+")
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+/// Returns the module that a task crate is grouped under, that is, the directories between
+/// `tasks` and the crate itself, joined with `/`. The crate in `tasks/loops/while` is in the
+/// `loops` module. `workspace_root` is the directory that contains the workspace manifest.
+///
+/// Returns `None` if the crate is directly inside the `tasks` directory.
+pub fn module(task: &LocalTask, workspace_root: &Path) -> Option<String> {
+    let crate_dir = task.manifest_path.parent()?;
+    let dir = pathdiff::diff_paths(crate_dir, workspace_root)?;
+    let module_dir = dir.strip_prefix("tasks").ok()?.parent()?;
+
+    if module_dir == Path::new("") {
+        return None;
+    }
+
+    let components = module_dir
+        .iter()
+        .map(|component| component.to_string_lossy())
+        .collect::<Vec<_>>();
+    Some(components.join("/"))
+}
+
+/// A local task whose crate isn't in the directory that `task_dir` derives from its title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamingMismatch {
+    /// The title of the task.
+    pub title: String,
+
+    /// The directory of the crate, relative to the repository root.
+    pub dir: PathBuf,
+
+    /// The directory that the crate should be moved to.
+    pub suggested_dir: PathBuf,
+}
+
+/// Checks whether a task crate is in the directory derived from its title. `workspace_root` is
+/// the directory that contains the workspace manifest.
+pub fn naming_mismatch(task: &LocalTask, workspace_root: &Path) -> Option<NamingMismatch> {
+    let crate_dir = task.manifest_path.parent()?;
+    let dir = pathdiff::diff_paths(crate_dir, workspace_root)?;
+    let suggested_dir = task_dir(&task.title);
+
+    if dir == suggested_dir {
+        None
+    } else {
+        Some(NamingMismatch {
+            title: task.title.clone(),
+            dir,
+            suggested_dir,
+        })
+    }
+}
+
+/// Converts a task title to a name that is safe to use for a file, following the conventions of
+/// `task_dir`.
+///
+/// Each component of the title is converted like a directory of `task_dir`, and the components are
+/// joined with hyphens instead of becoming subdirectories, so `Hello world/Text` becomes
+/// `hello-world-text`.
+pub fn slugify(title: &str) -> String {
+    title
+        .split('/')
+        .map(slug_component)
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Converts a single component of a task title to the form used in the repository.
+fn slug_component(title: &str) -> String {
+    let mut slug = String::new();
+
+    for c in title.chars().map(fold_diacritic) {
+        match c {
+            '\'' => {}
+            '+' => slug.push_str("-plus-"),
+            c if c.is_alphanumeric() => slug.extend(c.to_lowercase()),
+            _ => slug.push('-'),
+        }
+    }
+
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
 /// Parses the Rosetta Code URL from the package metadata in a task's crate manifest.
 fn parse_rosetta_url<P>(manifest_path: P) -> Result<Url, Error>
 where
     P: AsRef<Path>,
 {
-    let manifest: Value = fs::read_to_string(manifest_path)?.parse()?;
+    let manifest_path = manifest_path.as_ref();
+
+    let manifest: Value = fs::read_to_string(manifest_path)
+        .map_err(|err| IndexError::UnreadableFile {
+            path: manifest_path.to_owned(),
+            cause: err.to_string(),
+        })?
+        .parse()?;
 
     let url = manifest
         .get("package")
@@ -105,25 +996,452 @@ where
         .and_then(|m| m.get("rosettacode"))
         .and_then(|m| m.get("url"))
         .and_then(|u| u.as_str())
-        .ok_or_else(|| failure::err_msg("unexpected metadata format"))?;
+        .ok_or_else(|| Error::Parse("unexpected metadata format".into()))?;
 
     Ok(Url::parse(url)?)
 }
 
-/// Collect the paths to the source files of a given package.
-fn find_sources<P>(directory: P) -> Result<HashSet<PathBuf>, Error>
+/// True if a directory entry is hidden or contains build output, so that scans of the workspace
+/// skip it. The root of a walk is never skipped.
+fn is_hidden_or_build_output(entry: &DirEntry) -> bool {
+    let name = entry.file_name().to_string_lossy();
+    entry.depth() > 0 && (name.starts_with('.') || name == "target")
+}
+
+/// True if a directory entry matches the ignore patterns, relative to the workspace root.
+fn is_ignored_entry(entry: &DirEntry, root: &Path, ignore: &IgnorePatterns) -> bool {
+    entry.path().strip_prefix(root).ok().map_or(false, |path| {
+        path != Path::new("") && ignore.is_ignored(path, entry.file_type().is_dir())
+    })
+}
+
+/// Counts the files in the workspace that are ignored by the ignore patterns. Hidden directories
+/// and build output are not counted.
+pub fn count_ignored(root: &Path, ignore: &IgnorePatterns) -> Result<usize, Error> {
+    if ignore.is_empty() {
+        return Ok(0);
+    }
+
+    let mut count = 0;
+    let walker = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| !is_hidden_or_build_output(entry));
+
+    for entry in walker {
+        let entry = entry.map_err(|err| IndexError::UnreadableFile {
+            path: err.path().unwrap_or(root).to_owned(),
+            cause: err.to_string(),
+        })?;
+
+        if entry.file_type().is_file() && is_ignored_entry(&entry, root, ignore) {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Finds the crates in the `tasks` directory of a workspace that are not members of the
+/// workspace, so they are neither built nor counted. Returns their directories relative to the
+/// workspace root, sorted. Directories that match the ignore patterns are skipped.
+pub fn orphaned_crates<P>(manifest_path: P, ignore: &IgnorePatterns) -> Result<Vec<PathBuf>, Error>
 where
     P: AsRef<Path>,
 {
+    let manifest_path = manifest_path.as_ref();
+
+    if !manifest_path.is_file() {
+        return Err(IndexError::MissingManifest(manifest_path.to_owned()).into());
+    }
+
+    let workspace_root = manifest_path.parent().unwrap();
+
+    let metadata = cargo_metadata::metadata(Some(&manifest_path))
+        .map_err(|err| IndexError::Metadata(err.to_string()))?;
+    let member_dirs = metadata
+        .workspace_members
+        .iter()
+        .filter_map(|member| metadata.packages.iter().find(|p| p.name == member.name))
+        .map(|package| Path::new(&package.manifest_path))
+        .filter_map(Path::parent)
+        .map(Path::to_owned)
+        .collect::<HashSet<_>>();
+
+    let tasks_dir = workspace_root.join("tasks");
+    let walker = WalkDir::new(&tasks_dir).into_iter().filter_entry(|entry| {
+        !is_hidden_or_build_output(entry) && !is_ignored_entry(entry, workspace_root, ignore)
+    });
+
+    let mut orphans = vec![];
+
+    for entry in walker {
+        let entry = entry.map_err(|err| IndexError::UnreadableFile {
+            path: err.path().unwrap_or(&tasks_dir).to_owned(),
+            cause: err.to_string(),
+        })?;
+
+        if entry.file_name().to_string_lossy() != "Cargo.toml" {
+            continue;
+        }
+
+        let crate_dir = entry.path().parent().unwrap();
+        if !member_dirs.contains(crate_dir) {
+            let relative_dir = crate_dir.strip_prefix(workspace_root).unwrap_or(crate_dir);
+            orphans.push(relative_dir.to_owned());
+        }
+    }
+
+    orphans.sort();
+    Ok(orphans)
+}
+
+/// Collect the paths to the source files of a given package, which are the files that have a
+/// format in `options` and are not ignored. Ignore patterns are relative to `workspace_root`.
+fn find_sources(
+    directory: &Path,
+    workspace_root: &Path,
+    options: &ScanOptions,
+) -> Result<HashSet<PathBuf>, Error> {
     let mut sources = HashSet::new();
 
-    for entry in WalkDir::new(directory) {
-        let entry = entry?;
+    let walker = WalkDir::new(directory)
+        .into_iter()
+        .filter_entry(|entry| !is_ignored_entry(entry, workspace_root, &options.ignore));
+
+    for entry in walker {
+        let entry = entry.map_err(|err| IndexError::UnreadableFile {
+            path: err.path().unwrap_or(directory).to_owned(),
+            cause: err.to_string(),
+        })?;
 
-        if let Some("rs") = entry.path().extension().and_then(|s| s.to_str()) {
+        if options.formats.format_of(entry.path()).is_some() {
             sources.insert(entry.path().to_owned());
         }
     }
 
     Ok(sources)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use reqwest::Url;
+
+    use super::{
+        defines_main, dir_title, is_library_task, module, parse_aliases, read_cache, read_source,
+        referenced_crates, task_dir, todo_kind, write_cache, IgnorePatterns, LocalTask,
+        SourceFormat, SourceFormats, TodoKind,
+    };
+    use temp_dir::TempDir;
+
+    fn resource(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("resources")
+            .join(name)
+    }
+
+    #[test]
+    fn read_source_strips_bom() {
+        let source = read_source(resource("bom.rs")).unwrap();
+
+        assert_eq!(
+            ::diff::diff_kind(
+                Some(source.as_str()),
+                Some("fn main() {\n    println!(\"Hello, world!\");\n}\n")
+            ),
+            ::DiffKind::Identical
+        );
+    }
+
+    #[test]
+    fn read_source_invalid_utf8() {
+        let source = read_source(resource("invalid-utf8.rs")).unwrap();
+
+        assert_eq!(source, "fn main() {\n    // caf\u{FFFD}\n}\n");
+    }
+
+    #[test]
+    fn markdown_task() {
+        let formats = SourceFormats::new().with_extension("md", SourceFormat::Markdown);
+        assert_eq!(
+            formats.format_of(&resource("markdown-task.md")),
+            Some(SourceFormat::Markdown)
+        );
+        assert_eq!(formats.format_of(Path::new("notes.txt")), None);
+
+        let task = LocalTask {
+            package_name: String::from("markdown-task"),
+            manifest_path: resource("Cargo.toml"),
+            source: vec![resource("markdown-task.md")].into_iter().collect(),
+            formats,
+            dependencies: Default::default(),
+            url: Url::parse("http://rosettacode.org/wiki/Hello_world").unwrap(),
+            title: String::from("Hello world"),
+        };
+
+        assert_eq!(
+            task.code(),
+            "fn greeting() -> &'static str {\n    \"Hello, world!\"\n}\n\n\
+             fn main() {\n    println!(\"{}\", greeting());\n}\n"
+        );
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let dir = TempDir::new("rosetta-meta-cache-round-trip");
+        let cache_path = dir.path().join("cache.json");
+
+        let task = LocalTask {
+            package_name: String::from("hello-world"),
+            manifest_path: PathBuf::from("tasks/hello-world/Cargo.toml"),
+            source: vec![PathBuf::from("tasks/hello-world/src/main.rs")]
+                .into_iter()
+                .collect(),
+            formats: SourceFormats::new().with_extension("md", SourceFormat::Markdown),
+            dependencies: vec![String::from("rand")].into_iter().collect(),
+            url: Url::parse("http://rosettacode.org/wiki/Hello_world").unwrap(),
+            title: String::from("Hello world"),
+        };
+
+        write_cache(&cache_path, 42, &[task.clone()]).unwrap();
+
+        let cached = read_cache(&cache_path, 42).unwrap();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].title, task.title);
+        assert_eq!(cached[0].url, task.url);
+        assert_eq!(cached[0].source, task.source);
+        assert_eq!(cached[0].formats, task.formats);
+        assert_eq!(cached[0].dependencies, task.dependencies);
+
+        assert!(read_cache(&cache_path, 43).is_none());
+
+        fs::remove_file(&cache_path).unwrap();
+        assert!(read_cache(&cache_path, 42).is_none());
+    }
+
+    #[test]
+    fn aliases() {
+        let dir = TempDir::new("rosetta-meta-aliases");
+        let manifest_path = dir.path().join("Cargo.toml");
+        fs::write(
+            &manifest_path,
+            "[package]\n\
+             name = \"rust-rosetta\"\n\
+             \n\
+             [package.metadata.rosettacode.aliases]\n\
+             qsort = \"Sorting algorithms/Quicksort\"\n\
+             gcd = \"Greatest common divisor\"\n",
+        ).unwrap();
+
+        let aliases = parse_aliases(&manifest_path).unwrap();
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases["qsort"], "Sorting algorithms/Quicksort");
+        assert_eq!(aliases["gcd"], "Greatest common divisor");
+
+        fs::write(&manifest_path, "[package]\nname = \"rust-rosetta\"\n").unwrap();
+        assert!(parse_aliases(&manifest_path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn main_fn() {
+        assert!(defines_main("fn main() {}"));
+        assert!(defines_main("use std::io;\n\npub fn main () {\n}"));
+        assert!(!defines_main("fn main_loop() {}"));
+        assert!(!defines_main("// fn main() {}"));
+    }
+
+    #[test]
+    fn todo_kinds() {
+        assert_eq!(todo_kind("// TODO: Faster."), Some(TodoKind::Todo));
+        assert_eq!(todo_kind("let x = 1; //FIXME"), Some(TodoKind::Fixme));
+        assert_eq!(todo_kind("    todo!()"), Some(TodoKind::Unimplemented));
+        assert_eq!(
+            todo_kind("fn f() { unimplemented!(\"later\") }"),
+            Some(TodoKind::Unimplemented)
+        );
+        assert_eq!(todo_kind("let todo = \"TODO\";"), None);
+        assert_eq!(todo_kind("// TODOS"), None);
+    }
+
+    #[test]
+    fn todo_markers() {
+        let task = LocalTask {
+            package_name: String::from("todos"),
+            manifest_path: resource("Cargo.toml"),
+            source: vec![resource("todos.rs")].into_iter().collect(),
+            formats: Default::default(),
+            dependencies: Default::default(),
+            url: Url::parse("http://rosettacode.org/wiki/Hello_world").unwrap(),
+            title: String::from("Hello world"),
+        };
+
+        let markers = task
+            .todo_markers()
+            .into_iter()
+            .map(|marker| (marker.line, marker.kind))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            markers,
+            vec![
+                (1, TodoKind::Todo),
+                (3, TodoKind::Fixme),
+                (9, TodoKind::Unimplemented),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignore_patterns() {
+        let ignore = IgnorePatterns::parse(
+            "# Generated files\n\
+             *.bak\n\
+             /tasks/scratch\n\
+             drafts/\n\
+             !keep.bak\n",
+        ).unwrap();
+
+        assert!(ignore.is_ignored(Path::new("tasks/a/src/main.rs.bak"), false));
+        assert!(!ignore.is_ignored(Path::new("tasks/a/src/keep.bak"), false));
+        assert!(!ignore.is_ignored(Path::new("tasks/a/src/main.rs"), false));
+
+        assert!(ignore.is_ignored(Path::new("tasks/scratch"), true));
+        assert!(ignore.is_ignored(Path::new("tasks/scratch/src/main.rs"), false));
+        assert!(!ignore.is_ignored(Path::new("other/tasks/scratch"), true));
+
+        assert!(ignore.is_ignored(Path::new("tasks/drafts/src/main.rs"), false));
+        assert!(!ignore.is_ignored(Path::new("tasks/drafts"), false));
+
+        assert!(IgnorePatterns::parse("\n# nothing\n").unwrap().is_empty());
+        assert!(IgnorePatterns::parse("[").is_err());
+    }
+
+    #[test]
+    fn library_task() {
+        let lib = vec![PathBuf::from("src/lib.rs")].into_iter().collect();
+        let bin = vec![PathBuf::from("src/lib.rs"), PathBuf::from("src/main.rs")]
+            .into_iter()
+            .collect();
+
+        assert!(is_library_task(&lib, ""));
+        assert!(!is_library_task(&bin, ""));
+        assert!(is_library_task(&bin, "#![crate_type = \"lib\"]"));
+    }
+
+    #[test]
+    fn extern_crates() {
+        let code = r#"
+#![feature(test)]
+
+#[macro_use]
+extern crate serde_derive;
+extern crate num;
+extern crate rand as random;
+extern crate test;
+
+// extern crate commented_out;
+
+fn main() {}
+"#;
+
+        let crates = referenced_crates(code);
+        let crates = crates.iter().map(String::as_str).collect::<Vec<_>>();
+        assert_eq!(crates, vec!["num", "rand", "serde_derive"]);
+    }
+
+    #[test]
+    fn task_dir_simple() {
+        assert_eq!(task_dir("100 doors"), Path::new("tasks/100-doors"));
+        assert_eq!(task_dir("A+B"), Path::new("tasks/a-plus-b"));
+        assert_eq!(task_dir("Penney's game"), Path::new("tasks/penneys-game"));
+        assert_eq!(
+            task_dir("Arbitrary-precision integers (included)"),
+            Path::new("tasks/arbitrary-precision-integers-included")
+        );
+    }
+
+    #[test]
+    fn dir_title_reverse() {
+        assert_eq!(
+            dir_title(Path::new("tasks/100-doors")),
+            Some(String::from("100 doors"))
+        );
+        assert_eq!(
+            dir_title(Path::new("tasks/a-plus-b")),
+            Some(String::from("a+b"))
+        );
+        assert_eq!(
+            dir_title(Path::new("tasks/24-game/solve")),
+            Some(String::from("24 game/solve"))
+        );
+        assert_eq!(dir_title(Path::new("meta")), None);
+        assert_eq!(dir_title(Path::new("tasks")), None);
+    }
+
+    #[test]
+    fn task_module() {
+        let task = |dir: &str| LocalTask {
+            package_name: String::from("task"),
+            manifest_path: Path::new("/rosetta").join(dir).join("Cargo.toml"),
+            source: Default::default(),
+            formats: Default::default(),
+            dependencies: Default::default(),
+            url: Url::parse("http://rosettacode.org/wiki/").unwrap(),
+            title: String::from("Task"),
+        };
+        let root = Path::new("/rosetta");
+
+        assert_eq!(module(&task("tasks/100-doors"), root), None);
+        assert_eq!(
+            module(&task("tasks/loops/while"), root),
+            Some(String::from("loops"))
+        );
+        assert_eq!(
+            module(&task("tasks/a/b/c"), root),
+            Some(String::from("a/b"))
+        );
+        assert_eq!(module(&task("meta"), root), None);
+    }
+
+    #[test]
+    fn task_dir_subpage() {
+        assert_eq!(task_dir("24 game/Solve"), Path::new("tasks/24-game/solve"));
+        assert_eq!(
+            task_dir("Sorting algorithms/Quicksort"),
+            Path::new("tasks/sorting-algorithms/quicksort")
+        );
+        assert_eq!(
+            dir_title(&task_dir("Sorting algorithms/Quicksort")),
+            Some(String::from("sorting algorithms/quicksort"))
+        );
+    }
+
+    #[test]
+    fn task_dir_diacritics() {
+        assert_eq!(
+            task_dir("Vigenère cipher"),
+            Path::new("tasks/vigenere-cipher")
+        );
+    }
+
+    #[test]
+    fn slugify() {
+        use super::slugify;
+
+        assert_eq!(slugify("100 doors"), "100-doors");
+        assert_eq!(
+            slugify("Sorting algorithms/Quicksort"),
+            "sorting-algorithms-quicksort"
+        );
+        assert_eq!(
+            slugify("Category:Sorting Algorithms"),
+            "category-sorting-algorithms"
+        );
+        assert_eq!(slugify("A+B"), "a-plus-b");
+        assert_eq!(slugify("Vigenère cipher"), "vigenere-cipher");
+        assert_eq!(slugify("Hello world/Text/"), "hello-world-text");
+        assert_eq!(slugify("Ελληνικά"), "ελληνικά");
+    }
+}
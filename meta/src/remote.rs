@@ -1,17 +1,72 @@
 //! Utilities for interacting with tasks implemented on the Rosetta Code wiki.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use percent_encoding::{self, QUERY_ENCODE_SET};
 use regex::Regex;
 use reqwest::Url;
 
+use diff;
+use error::Error;
+
 lazy_static!{
-    /// Extracts code from the first Rust section from Rosetta Code wiki markup.
-    static ref RUST_WIKI_SECTION_RE: Regex =
-        Regex::new(r"==\{\{header\|Rust\}\}==(?s:.*?)<lang rust>((?s:.*?))</lang>").unwrap();
+    /// Matches the header of the Rust section in Rosetta Code wiki markup.
+    static ref RUST_HEADER_RE: Regex =
+        Regex::new(r"(?i)==\s*\{\{\s*header\s*\|\s*Rust\s*\}\}\s*==").unwrap();
+
+    /// Matches a block of Rust code, capturing the code itself.
+    ///
+    /// The wiki uses both `<lang rust>` and `<syntaxhighlight lang="rust">` tags. The language may
+    /// be quoted or capitalized, and the tags may contain extra attributes and whitespace.
+    static ref RUST_CODE_BLOCK_RE: Regex = Regex::new(
+        r#"(?is)<\s*(?:lang\s+(?:"rust"|'rust'|rust\b)|(?:syntaxhighlight|source)\b[^>]*?\blang\s*=\s*(?:"rust"|'rust'|rust\b))[^>]*>(.*?)<\s*/\s*(?:lang|syntaxhighlight|source)\s*>"#
+    ).unwrap();
+
+    /// Matches the header of a language section, capturing the name of the language. Headers may
+    /// give a display name after another `|`, or be followed by text such as `(version 2)`.
+    static ref LANGUAGE_HEADER_RE: Regex = Regex::new(
+        r"(?m)^==\s*\{\{\s*[Hh]eader\s*\|\s*([^}|]+?)\s*(?:\|[^}]*)?\}\}[^=\n]*=="
+    ).unwrap();
+
+    /// Matches a header of any level, such as `==Python==` or `===Version 1===`.
+    static ref SECTION_HEADER_RE: Regex = Regex::new(r"(?m)^=+[^=\n].*=+[ \t]*$").unwrap();
+
+    /// Matches a subheader such as `===Version 1===`, capturing its text.
+    static ref SUBHEADER_RE: Regex = Regex::new(r"(?m)^===+\s*(.*?)\s*===+\s*$").unwrap();
+
+    /// Matches the start of the next top-level section header.
+    static ref NEXT_HEADER_RE: Regex = Regex::new(r"(?m)^==[^=]").unwrap();
+
+    /// Matches a code block tag that declares a language.
+    static ref LANG_TAG_RE: Regex = Regex::new(
+        r"(?i)<\s*(?:lang\s+[^>\s]|(?:syntaxhighlight|source)\b[^>]*\blang\s*=)"
+    ).unwrap();
+
+    /// Matches tags that may surround code that isn't marked as Rust.
+    static ref MARKUP_TAG_RE: Regex =
+        Regex::new(r"(?i)<\s*/?\s*(?:lang|pre|code)\b[^>]*>").unwrap();
+
+    /// Matches a template such as `{{task}}` that doesn't contain other templates.
+    static ref TEMPLATE_RE: Regex = Regex::new(r"\{\{[^{}]*\}\}").unwrap();
+
+    /// Matches a link that adds the page to a category or embeds a file, rather than being
+    /// displayed as text.
+    static ref META_LINK_RE: Regex =
+        Regex::new(r"\[\[(?i:category|file|image):[^\]]*\]\]").unwrap();
+
+    /// Matches a link to another page, capturing the text that the link is displayed as.
+    static ref WIKI_LINK_RE: Regex = Regex::new(r"\[\[(?:[^|\]]*\|)?([^\]]*)\]\]").unwrap();
+
+    /// Matches any HTML tag.
+    static ref HTML_TAG_RE: Regex = Regex::new(r"<[^>]*>").unwrap();
 }
 
+/// The URL of the MediaWiki API endpoint of Rosetta Code.
+pub const API_URL: &str = "http://rosettacode.org/mw/api.php";
+
+/// The URL of the script that renders and edits pages on Rosetta Code.
+pub const INDEX_URL: &str = "http://rosettacode.org/mw/index.php";
+
 define_encode_set! {
     /// Encoding set used for Rosetta Code URLs.
     ///
@@ -20,12 +75,50 @@ define_encode_set! {
     pub ROSETTA_ENCODE_SET = [QUERY_ENCODE_SET] | { '+' }
 }
 
+/// The namespaces of the wiki, other than the main namespace that contains the tasks.
+const NAMESPACES: &[&str] = &[
+    "Talk",
+    "User",
+    "User talk",
+    "Rosetta Code",
+    "Rosetta Code talk",
+    "File",
+    "File talk",
+    "MediaWiki",
+    "MediaWiki talk",
+    "Template",
+    "Template talk",
+    "Help",
+    "Help talk",
+    "Category",
+    "Category talk",
+    "Special",
+];
+
+/// Describes how the Rust code of a task was extracted from the wiki markup of its page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractionMethod {
+    /// The code was found in a code block that is marked as Rust.
+    CodeBlock,
+
+    /// The Rust section has no such code block, so the code was salvaged from the text of the
+    /// section instead.
+    Fallback,
+}
+
 /// Represents a task implemented on the Rosetta Code wiki.
 #[derive(Debug, Clone)]
 pub struct RemoteTask {
     title: String,
     url: Url,
     source: Option<String>,
+    extraction_method: Option<ExtractionMethod>,
+    variants: Vec<(String, String)>,
+    page_size: usize,
+    languages: Vec<String>,
+    rust_section: Option<usize>,
+    description: Option<String>,
 }
 
 impl RemoteTask {
@@ -44,6 +137,76 @@ impl RemoteTask {
     pub fn source(&self) -> Option<String> {
         self.source.clone()
     }
+
+    /// Replaces the Rust code of the task, such as with the code of an older revision of its page.
+    /// The other details of the page, such as its variants and languages, are kept.
+    pub fn with_source(mut self, source: Option<String>) -> RemoteTask {
+        if source.is_none() {
+            self.extraction_method = None;
+        }
+        self.source = source;
+        self
+    }
+
+    /// Returns every Rust code block on the page, along with the name of its subsection. See
+    /// `extract_rust_variants`.
+    pub fn variants(&self) -> Vec<(String, String)> {
+        self.variants.clone()
+    }
+
+    /// True if the page has a Rust section, even if no code could be extracted from it.
+    pub fn has_rust_section(&self) -> bool {
+        self.rust_section.is_some()
+    }
+
+    /// True if the page has a Rust section, but no Rust code could be extracted from it. This
+    /// usually means that the section uses markup that the parser does not understand.
+    pub fn is_unparseable(&self) -> bool {
+        self.has_rust_section() && self.source.is_none()
+    }
+
+    /// Returns how the Rust code was extracted from the page, or `None` if there is no Rust code.
+    pub fn extraction_method(&self) -> Option<ExtractionMethod> {
+        self.extraction_method
+    }
+
+    /// Returns the description of the task as plain text, or `None` if the page has none. See
+    /// `description`.
+    pub fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
+    /// Returns the languages that the task is implemented in on the wiki, in the order of their
+    /// sections on the page.
+    pub fn languages(&self) -> Vec<String> {
+        self.languages.clone()
+    }
+
+    /// Returns the number of languages that the task is implemented in on the wiki.
+    pub fn implementation_count(&self) -> usize {
+        self.languages.len()
+    }
+
+    /// Returns the URL that edits the Rust section of the task on the wiki, or the whole page if
+    /// the page has no Rust section.
+    pub fn edit_url(&self) -> Url {
+        let mut url = Url::parse(INDEX_URL).unwrap();
+        url.query_pairs_mut()
+            .append_pair("title", &self.title)
+            .append_pair("action", "edit");
+
+        if let Some(section) = self.rust_section {
+            url.query_pairs_mut()
+                .append_pair("section", &section.to_string());
+        }
+
+        url
+    }
+
+    /// Returns the size of the wiki markup of the whole page, in bytes.
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,7 +223,8 @@ pub struct Pages {
 /// Individual page in the query API response.
 #[derive(Debug, Deserialize)]
 pub struct Page {
-    #[serde(rename = "pageid")]
+    /// The ID of the page. Missing pages don't have one.
+    #[serde(rename = "pageid", default)]
     pub id: i32,
 
     pub title: String,
@@ -73,6 +237,365 @@ pub struct Page {
 pub struct Revision {
     #[serde(rename = "*")]
     pub content: String,
+
+    /// The ID of the revision, if it was requested with `rvprop=ids`.
+    #[serde(default)]
+    pub revid: u64,
+}
+
+/// The response to a `list=categorymembers` query.
+#[derive(Debug, Deserialize)]
+pub struct CategoryResponse {
+    /// Parameters that must be added to the next request to retrieve more results. If there are no
+    /// more results, this field is missing.
+    #[serde(rename = "continue")]
+    pub continue_params: Option<BTreeMap<String, String>>,
+
+    pub query: CategoryMembers,
+}
+
+/// Intermediate container in the category members API response.
+#[derive(Debug, Deserialize)]
+pub struct CategoryMembers {
+    #[serde(rename = "categorymembers")]
+    pub members: Vec<CategoryMember>,
+}
+
+/// Individual page in the category members API response.
+#[derive(Debug, Deserialize)]
+pub struct CategoryMember {
+    pub title: String,
+
+    /// The namespace of the page. Tasks are in the main namespace, `0`.
+    #[serde(default)]
+    pub ns: i32,
+}
+
+/// The response to a `prop=categories` query.
+#[derive(Debug, Deserialize)]
+pub struct CategoriesResponse {
+    /// Parameters that must be added to the next request to retrieve more results. If there are no
+    /// more results, this field is missing.
+    #[serde(rename = "continue")]
+    pub continue_params: Option<BTreeMap<String, String>>,
+
+    pub query: CategoryPages,
+}
+
+/// Intermediate container in the categories API response.
+#[derive(Debug, Deserialize)]
+pub struct CategoryPages {
+    pub pages: HashMap<i32, CategoryPage>,
+}
+
+/// Individual page in the categories API response.
+#[derive(Debug, Deserialize)]
+pub struct CategoryPage {
+    pub title: String,
+
+    /// The categories of the page. Pages that are split over several responses only list some of
+    /// their categories in each.
+    #[serde(default)]
+    pub categories: Vec<CategoryMember>,
+}
+
+/// The response to a `prop=revisions&rvprop=ids` query.
+#[derive(Debug, Deserialize)]
+pub struct RevisionIdsResponse {
+    pub query: RevisionIdPages,
+}
+
+/// Intermediate container in the revision IDs API response.
+#[derive(Debug, Deserialize)]
+pub struct RevisionIdPages {
+    pub pages: HashMap<i32, RevisionIdPage>,
+}
+
+/// Individual page in the revision IDs API response. Missing pages have no revisions.
+#[derive(Debug, Deserialize)]
+pub struct RevisionIdPage {
+    pub title: String,
+
+    #[serde(default)]
+    pub revisions: Vec<RevisionId>,
+}
+
+/// The ID of a revision of a page.
+#[derive(Debug, Deserialize)]
+pub struct RevisionId {
+    pub revid: u64,
+}
+
+/// Returns the ID of the latest revision of each of the given pages, without their content. Pages
+/// that don't exist are left out.
+///
+/// The pages are queried in batches. `query` is called to perform each request.
+pub fn latest_revisions<F>(titles: &[String], mut query: F) -> Result<BTreeMap<String, u64>, Error>
+where
+    F: FnMut(&Url) -> Result<RevisionIdsResponse, Error>,
+{
+    let mut revisions = BTreeMap::new();
+
+    for batch in titles.chunks(50) {
+        let mut request = Url::parse(API_URL).unwrap();
+        request
+            .query_pairs_mut()
+            .append_pair("action", "query")
+            .append_pair("format", "json")
+            .append_pair("prop", "revisions")
+            .append_pair("rvprop", "ids")
+            .append_pair("titles", &batch.join("|"));
+
+        let response = query(&request)?;
+
+        for page in response.query.pages.into_iter().map(|(_, page)| page) {
+            if let Some(revision) = page.revisions.first() {
+                revisions.insert(page.title, revision.revid);
+            }
+        }
+    }
+
+    Ok(revisions)
+}
+
+/// Response of a query that resolves the redirects of some titles.
+#[derive(Debug, Deserialize)]
+pub struct RedirectsResponse {
+    pub query: RedirectsQuery,
+}
+
+/// Intermediate container in the redirects API response. Titles that are neither normalized nor
+/// redirected are not listed.
+#[derive(Debug, Deserialize)]
+pub struct RedirectsQuery {
+    #[serde(default)]
+    pub normalized: Vec<Redirect>,
+
+    #[serde(default)]
+    pub redirects: Vec<Redirect>,
+}
+
+/// A title that the wiki resolved to another title.
+#[derive(Debug, Deserialize)]
+pub struct Redirect {
+    pub from: String,
+    pub to: String,
+}
+
+/// Returns the title that each of the given pages redirects to, following chains of redirects.
+/// Pages that aren't redirects are left out.
+///
+/// The pages are queried in batches. `query` is called to perform each request.
+pub fn redirect_targets<F>(
+    titles: &[String],
+    mut query: F,
+) -> Result<BTreeMap<String, String>, Error>
+where
+    F: FnMut(&Url) -> Result<RedirectsResponse, Error>,
+{
+    let mut targets = BTreeMap::new();
+
+    for batch in titles.chunks(50) {
+        let mut request = Url::parse(API_URL).unwrap();
+        request
+            .query_pairs_mut()
+            .append_pair("action", "query")
+            .append_pair("format", "json")
+            .append_pair("redirects", "1")
+            .append_pair("titles", &batch.join("|"));
+
+        let response = query(&request)?;
+
+        let normalized = response
+            .query
+            .normalized
+            .into_iter()
+            .map(|title| (title.from, title.to))
+            .collect::<HashMap<_, _>>();
+        let redirects = response
+            .query
+            .redirects
+            .into_iter()
+            .map(|redirect| (redirect.from, redirect.to))
+            .collect::<HashMap<_, _>>();
+
+        for title in batch {
+            let mut target = normalized.get(title).unwrap_or(title);
+            let mut redirected = false;
+
+            // No more redirects are followed than there are, in case they form a cycle.
+            for _ in 0..redirects.len() {
+                match redirects.get(target) {
+                    Some(next) => {
+                        target = next;
+                        redirected = true;
+                    }
+                    None => break,
+                }
+            }
+
+            if redirected && target != title {
+                targets.insert(title.clone(), target.clone());
+            }
+        }
+    }
+
+    Ok(targets)
+}
+
+/// Returns the Rust code of the pages at the given revisions, by the title of each page. Pages
+/// whose revision has no Rust code map to `None`, and revisions that don't exist are left out.
+///
+/// The revisions are queried in batches. `query` is called to perform each request.
+pub fn revision_code<F>(
+    revisions: &[u64],
+    mut query: F,
+) -> Result<BTreeMap<String, Option<String>>, Error>
+where
+    F: FnMut(&Url) -> Result<Response, Error>,
+{
+    let mut code = BTreeMap::new();
+
+    for batch in revisions.chunks(50) {
+        let ids = batch.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        let mut request = Url::parse(API_URL).unwrap();
+        request
+            .query_pairs_mut()
+            .append_pair("action", "query")
+            .append_pair("format", "json")
+            .append_pair("prop", "revisions")
+            .append_pair("rvprop", "content")
+            .append_pair("revids", &ids.join("|"));
+
+        let response = query(&request)?;
+
+        for page in response.query.pages.into_iter().map(|(_, page)| page) {
+            if let Some(revision) = page.revisions.first() {
+                code.insert(page.title, extract_rust_code(&revision.content));
+            }
+        }
+    }
+
+    Ok(code)
+}
+
+/// Returns the categories of each of the given pages, without the `Category:` prefix. Hidden
+/// categories are skipped.
+///
+/// The pages are queried in batches, following the continuation parameters of each response.
+/// `query` is called to perform each request.
+pub fn page_categories<F>(
+    titles: &[String],
+    mut query: F,
+) -> Result<BTreeMap<String, BTreeSet<String>>, Error>
+where
+    F: FnMut(&Url) -> Result<CategoriesResponse, Error>,
+{
+    let mut categories = BTreeMap::new();
+
+    for batch in titles.chunks(50) {
+        let mut continue_params = BTreeMap::new();
+        continue_params.insert(String::from("continue"), String::new());
+        let mut continue_params = Some(continue_params);
+
+        while let Some(params) = continue_params.take() {
+            let mut request = Url::parse(API_URL).unwrap();
+            request
+                .query_pairs_mut()
+                .append_pair("action", "query")
+                .append_pair("format", "json")
+                .append_pair("prop", "categories")
+                .append_pair("clshow", "!hidden")
+                .append_pair("cllimit", "max")
+                .append_pair("titles", &batch.join("|"))
+                .extend_pairs(&params);
+
+            let response = query(&request)?;
+
+            for page in response.query.pages.into_iter().map(|(_, page)| page) {
+                categories
+                    .entry(page.title)
+                    .or_insert_with(BTreeSet::new)
+                    .extend(
+                        page.categories.into_iter().map(|category| {
+                            category.title.trim_left_matches("Category:").to_owned()
+                        }),
+                    );
+            }
+
+            continue_params = response.continue_params;
+        }
+    }
+
+    Ok(categories)
+}
+
+/// Returns the titles of every page in a category.
+///
+/// The API only returns a limited number of results per request, so this function follows the
+/// continuation parameters of each response until all members have been retrieved. `query` is
+/// called to perform each request.
+///
+/// Members outside of the main namespace, such as subcategories, are not tasks and are skipped.
+pub fn category_members<F>(category: &str, mut query: F) -> Result<Vec<String>, Error>
+where
+    F: FnMut(&Url) -> Result<CategoryResponse, Error>,
+{
+    let mut titles = vec![];
+
+    let mut continue_params = BTreeMap::new();
+    continue_params.insert(String::from("continue"), String::new());
+    let mut continue_params = Some(continue_params);
+
+    while let Some(params) = continue_params.take() {
+        let mut request = Url::parse(API_URL).unwrap();
+        request
+            .query_pairs_mut()
+            .append_pair("action", "query")
+            .append_pair("format", "json")
+            .append_pair("list", "categorymembers")
+            .append_pair("cmtitle", &format!("Category:{}", category))
+            .append_pair("cmlimit", "500")
+            .extend_pairs(&params);
+
+        let response = query(&request)?;
+        titles.extend(
+            response
+                .query
+                .members
+                .into_iter()
+                .filter(|member| member.ns == 0 && namespace(&member.title).is_none())
+                .map(|member| member.title),
+        );
+        continue_params = response.continue_params;
+    }
+
+    Ok(titles)
+}
+
+/// Returns the namespace of a page title, such as `Category` for `Category:Sorting Algorithms`.
+///
+/// Returns `None` for pages in the main namespace, which is where tasks live. Titles of tasks may
+/// contain colons themselves, so only the known namespaces of the wiki are recognized.
+pub fn namespace(title: &str) -> Option<&'static str> {
+    let prefix = title[..title.find(':')?].replace("_", " ");
+
+    NAMESPACES
+        .iter()
+        .find(|namespace| namespace.eq_ignore_ascii_case(prefix.trim()))
+        .cloned()
+}
+
+/// Capitalizes the first letter of a title, like the wiki does. Apart from the first letter, titles
+/// on the wiki are case-sensitive.
+pub fn normalize_title(title: &str) -> String {
+    let mut chars = title.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
 
 /// Transforms a URL-encoded task title from the wiki to a human-readable task title.
@@ -89,28 +612,237 @@ pub fn encode_title(title: &str) -> String {
     encoded.collect()
 }
 
+/// Extracts the first block of code from the Rust section of a page's wiki markup.
+pub fn extract_rust_code(content: &str) -> Option<String> {
+    extract_rust_code_with_method(content).map(|(code, _)| code)
+}
+
+/// Extracts the Rust code from a page's wiki markup, and reports how it was found.
+///
+/// The first code block marked as Rust after the Rust header is preferred. If there is none, the
+/// text of the Rust section is used instead, without wiki templates, links, subheaders and the
+/// tags around the code. Sections that contain code blocks in other languages are not salvaged.
+pub fn extract_rust_code_with_method(content: &str) -> Option<(String, ExtractionMethod)> {
+    let section = &content[RUST_HEADER_RE.find(content)?.end()..];
+    let end = NEXT_HEADER_RE
+        .find(section)
+        .map_or(section.len(), |header| header.start());
+    let section = &section[..end];
+
+    if let Some(captures) = RUST_CODE_BLOCK_RE.captures(section) {
+        return Some((captures[1].to_owned(), ExtractionMethod::CodeBlock));
+    }
+
+    extract_fallback(section).map(|code| (code, ExtractionMethod::Fallback))
+}
+
+/// Extracts every Rust code block in the Rust section of a page's wiki markup, along with a name
+/// for each block.
+///
+/// Some pages have several versions of the code under subheaders, such as `===Version 1===`.
+/// Blocks are named after the subheader that they are under. Blocks before the first subheader are
+/// named by their position in the section instead, such as `#1`. If a subsection contains several
+/// blocks, all but the first have their position in the subsection appended, as in
+/// `Version 1 (2)`.
+pub fn extract_rust_variants(content: &str) -> Vec<(String, String)> {
+    let section = match RUST_HEADER_RE.find(content) {
+        Some(header) => &content[header.end()..],
+        None => return vec![],
+    };
+    let end = NEXT_HEADER_RE
+        .find(section)
+        .map_or(section.len(), |header| header.start());
+    let section = &section[..end];
+
+    let subheaders = SUBHEADER_RE
+        .captures_iter(section)
+        .map(|captures| (captures.get(0).unwrap().start(), captures[1].to_owned()))
+        .collect::<Vec<_>>();
+
+    let mut blocks_per_subheader = HashMap::new();
+    let mut variants = vec![];
+
+    for (index, captures) in RUST_CODE_BLOCK_RE.captures_iter(section).enumerate() {
+        let start = captures.get(0).unwrap().start();
+        let subheader = subheaders
+            .iter()
+            .filter(|&&(position, _)| position < start)
+            .last()
+            .map(|&(_, ref name)| name.clone());
+
+        let name = match subheader {
+            Some(subheader) => {
+                let count = blocks_per_subheader.entry(subheader.clone()).or_insert(0);
+                *count += 1;
+
+                if *count == 1 {
+                    subheader
+                } else {
+                    format!("{} ({})", subheader, count)
+                }
+            }
+            None => format!("#{}", index + 1),
+        };
+
+        variants.push((name, captures[1].to_owned()));
+    }
+
+    variants
+}
+
+/// Collapses variants whose code is nearly identical to the code of an earlier variant, keeping
+/// the first of them.
+///
+/// Two variants are nearly identical if the `diff::similarity` of their code is at least
+/// `threshold`, so a threshold of `1.0` only collapses variants with the same lines.
+pub fn dedup_variants(variants: Vec<(String, String)>, threshold: f64) -> Vec<(String, String)> {
+    let mut distinct: Vec<(String, String)> = vec![];
+
+    for (name, code) in variants {
+        let duplicate = distinct
+            .iter()
+            .any(|&(_, ref kept)| diff::similarity(kept, &code) >= threshold);
+
+        if !duplicate {
+            distinct.push((name, code));
+        }
+    }
+
+    distinct
+}
+
+/// Salvages the code from the text of a Rust section that has no Rust code block. The section
+/// must not include the headers of the sections after it.
+fn extract_fallback(section: &str) -> Option<String> {
+    if LANG_TAG_RE.is_match(section) {
+        return None;
+    }
+
+    let text = MARKUP_TAG_RE.replace_all(section, "");
+    let code = text
+        .lines()
+        .filter(|line| {
+            let line = line.trim();
+            !(line.starts_with("{{") || line.starts_with("[[") || line.starts_with('='))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let code = code.trim();
+    if code.is_empty() {
+        None
+    } else {
+        Some(code.to_owned())
+    }
+}
+
+/// Returns the description of a task, which is the prose before the first language section of its
+/// page, as plain text.
+///
+/// Templates, such as `{{task}}`, category links and HTML tags are removed, other links are
+/// replaced by their text and headers only separate paragraphs. Lines of a paragraph are joined
+/// with spaces, and paragraphs are separated by blank lines. Returns `None` if there is no prose.
+pub fn description(wiki_text: &str) -> Option<String> {
+    let end = LANGUAGE_HEADER_RE
+        .find(wiki_text)
+        .map_or(wiki_text.len(), |header| header.start());
+
+    // Templates can be nested, so the innermost ones are removed until none are left.
+    let mut text = wiki_text[..end].to_owned();
+    while TEMPLATE_RE.is_match(&text) {
+        text = TEMPLATE_RE.replace_all(&text, "").into_owned();
+    }
+    let text = META_LINK_RE.replace_all(&text, "");
+    let text = WIKI_LINK_RE.replace_all(&text, "$1");
+    let text = HTML_TAG_RE.replace_all(&text, "");
+    let text = text.replace("'''", "").replace("''", "");
+
+    let mut paragraphs = vec![];
+    let mut paragraph = vec![];
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || SECTION_HEADER_RE.is_match(line) {
+            if !paragraph.is_empty() {
+                paragraphs.push(paragraph.join(" "));
+                paragraph.clear();
+            }
+        } else {
+            paragraph.push(line);
+        }
+    }
+    if !paragraph.is_empty() {
+        paragraphs.push(paragraph.join(" "));
+    }
+
+    if paragraphs.is_empty() {
+        None
+    } else {
+        Some(paragraphs.join("\n\n"))
+    }
+}
+
+/// Returns the number of the Rust section in the wiki markup of a task, as used by the `section`
+/// parameter of the wiki. Sections are numbered from 1 in the order of their headers, regardless
+/// of the level of the header.
+pub fn rust_section(wiki_text: &str) -> Option<usize> {
+    let rust_header = RUST_HEADER_RE.find(wiki_text)?;
+
+    let index = SECTION_HEADER_RE.find_iter(wiki_text).position(|header| {
+        header.start() <= rust_header.start() && rust_header.end() <= header.end()
+    })?;
+    Some(index + 1)
+}
+
+/// Returns the languages that have a section in the wiki markup of a task, in the order that they
+/// appear. Languages with several sections are only listed once, regardless of capitalization.
+pub fn languages(wiki_text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+
+    LANGUAGE_HEADER_RE
+        .captures_iter(wiki_text)
+        .map(|captures| captures[1].to_owned())
+        .filter(|language| seen.insert(language.to_lowercase()))
+        .collect()
+}
+
 pub fn parse_tasks(response: &Response) -> Vec<RemoteTask> {
     response
         .query
         .pages
         .values()
-        .map(|page| {
-            let source = RUST_WIKI_SECTION_RE
-                .captures(&page.revisions[0].content)
-                .map(|captures| captures.get(1).unwrap())
-                .map(|m| m.as_str().to_owned());
-
-            let title = page.title.clone();
-            let url = Url::parse(&format!(
-                "http://rosettacode.org/wiki/{}",
-                encode_title(&title)
-            )).unwrap();
-
-            RemoteTask { title, url, source }
+        .filter_map(|page| {
+            // Pages that don't exist have no revisions.
+            let content = &page.revisions.first()?.content;
+            Some(parse_page(&page.title, content))
         })
         .collect()
 }
 
+/// Parses the task on a page from the wiki markup of the page.
+pub fn parse_page(title: &str, content: &str) -> RemoteTask {
+    let (source, extraction_method) = match extract_rust_code_with_method(content) {
+        Some((source, method)) => (Some(source), Some(method)),
+        None => (None, None),
+    };
+
+    let url = Url::parse(&format!(
+        "http://rosettacode.org/wiki/{}",
+        encode_title(title)
+    )).unwrap();
+
+    RemoteTask {
+        title: title.to_owned(),
+        url,
+        source,
+        extraction_method,
+        variants: extract_rust_variants(content),
+        page_size: content.len(),
+        languages: languages(content),
+        rust_section: rust_section(content),
+        description: description(content),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -122,6 +854,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn encode_namespaced() {
+        use super::encode_title;
+        assert_eq!(
+            encode_title("Category:Sorting Algorithms"),
+            "Category:Sorting_Algorithms"
+        );
+    }
+
+    #[test]
+    fn encode_subpage() {
+        use super::{decode_title, encode_title};
+        assert_eq!(
+            encode_title("Sorting algorithms/Quicksort"),
+            "Sorting_algorithms/Quicksort"
+        );
+        assert_eq!(
+            decode_title("Sorting_algorithms/Quicksort"),
+            "Sorting algorithms/Quicksort"
+        );
+        assert_eq!(
+            decode_title("Sorting_algorithms%2FQuicksort"),
+            "Sorting algorithms/Quicksort"
+        );
+    }
+
+    #[test]
+    fn namespace() {
+        use super::namespace;
+        assert_eq!(namespace("Category:Sorting Algorithms"), Some("Category"));
+        assert_eq!(
+            namespace("Category_talk:Sorting Algorithms"),
+            Some("Category talk")
+        );
+        assert_eq!(namespace("talk:100 doors"), Some("Talk"));
+        assert_eq!(namespace("Sorting algorithms/Quicksort"), None);
+        assert_eq!(namespace("Not a namespace: a task"), None);
+    }
+
     #[test]
     fn decode() {
         use super::decode_title;
@@ -131,6 +902,411 @@ mod tests {
         );
     }
 
+    #[test]
+    fn category_members_follows_continuation() {
+        const FIRST_PAGE: &str = r#"{
+            "batchcomplete": "",
+            "continue": { "cmcontinue": "page|4b4e49474854|1234", "continue": "-||" },
+            "query": {
+                "categorymembers": [
+                    { "pageid": 1, "ns": 0, "title": "100 doors" },
+                    { "pageid": 2, "ns": 0, "title": "A+B" }
+                ]
+            }
+        }"#;
+
+        const SECOND_PAGE: &str = r#"{
+            "batchcomplete": "",
+            "query": {
+                "categorymembers": [
+                    { "pageid": 3, "ns": 0, "title": "Knight's tour" },
+                    { "pageid": 4, "ns": 14, "title": "Category:Sorting Algorithms" }
+                ]
+            }
+        }"#;
+
+        let mut requests = 0;
+
+        let titles = super::category_members("Programming Tasks", |url| {
+            requests += 1;
+
+            let page = if url.query_pairs().any(|(key, _)| key == "cmcontinue") {
+                SECOND_PAGE
+            } else {
+                FIRST_PAGE
+            };
+
+            Ok(::serde_json::from_str(page)?)
+        }).unwrap();
+
+        assert_eq!(titles, vec!["100 doors", "A+B", "Knight's tour"]);
+        assert_eq!(requests, 2);
+    }
+
+    mod extract_rust_code {
+        use super::super::{
+            dedup_variants, extract_rust_code, extract_rust_code_with_method,
+            extract_rust_variants, ExtractionMethod,
+        };
+
+        fn extract(tag: &str, closing_tag: &str) -> Option<String> {
+            let content = format!(
+                "=={{{{header|Python}}}}==\n<lang python>pass</lang>\n\
+                 =={{{{header|Rust}}}}==\n{}fn main() {{}}{}\n",
+                tag, closing_tag
+            );
+            extract_rust_code(&content)
+        }
+
+        fn assert_extracted(tag: &str, closing_tag: &str) {
+            assert_eq!(
+                extract(tag, closing_tag),
+                Some(String::from("fn main() {}")),
+                "could not extract code from {}",
+                tag
+            );
+        }
+
+        #[test]
+        fn lang_tag() {
+            assert_extracted("<lang rust>", "</lang>");
+            assert_extracted("<lang Rust>", "</lang>");
+            assert_extracted("<lang rust >", "</lang >");
+        }
+
+        #[test]
+        fn syntaxhighlight_tag() {
+            assert_extracted("<syntaxhighlight lang=\"rust\">", "</syntaxhighlight>");
+            assert_extracted("<syntaxhighlight lang='rust'>", "</syntaxhighlight>");
+            assert_extracted("<syntaxhighlight lang=\"Rust\">", "</syntaxhighlight>");
+            assert_extracted("<syntaxhighlight lang=rust>", "</syntaxhighlight>");
+        }
+
+        #[test]
+        fn extra_attributes() {
+            assert_extracted("<syntaxhighlight lang=\"rust\" line>", "</syntaxhighlight>");
+            assert_extracted("<syntaxhighlight line lang=\"rust\">", "</syntaxhighlight>");
+        }
+
+        #[test]
+        fn whitespace_in_tag() {
+            assert_extracted(
+                "< syntaxhighlight  lang = \"rust\" >",
+                "</ syntaxhighlight >",
+            );
+        }
+
+        #[test]
+        fn other_languages() {
+            assert_eq!(extract("<lang rusty>", "</lang>"), None);
+            assert_eq!(
+                extract(
+                    "<syntaxhighlight lang=\"rustscript\">",
+                    "</syntaxhighlight>"
+                ),
+                None
+            );
+        }
+
+        #[test]
+        fn code_block_method() {
+            assert_eq!(
+                extract_rust_code_with_method(
+                    "=={{header|Rust}}==\n<lang rust>fn main() {}</lang>"
+                ),
+                Some((String::from("fn main() {}"), ExtractionMethod::CodeBlock))
+            );
+        }
+
+        #[test]
+        fn fallback_pre_tag() {
+            let content = "=={{header|Rust}}==\n\
+                           {{works with|Rust|1.0}}\n\
+                           <pre>\nfn main() {}\n</pre>\n\
+                           =={{header|Scala}}==\n<lang scala>object Main</lang>\n";
+
+            assert_eq!(
+                extract_rust_code_with_method(content),
+                Some((String::from("fn main() {}"), ExtractionMethod::Fallback))
+            );
+        }
+
+        #[test]
+        fn code_block_in_later_section() {
+            let content = "=={{header|Rust}}==\n\
+                           <pre>\nfn main() {}\n</pre>\n\
+                           =={{header|Scala}}==\n<lang rust>not rust</lang>\n";
+
+            assert_eq!(
+                extract_rust_code_with_method(content),
+                Some((String::from("fn main() {}"), ExtractionMethod::Fallback))
+            );
+        }
+
+        #[test]
+        fn fallback_untagged_lang() {
+            assert_eq!(
+                extract_rust_code_with_method("=={{header|Rust}}==\n<lang>fn main() {}</lang>\n"),
+                Some((String::from("fn main() {}"), ExtractionMethod::Fallback))
+            );
+        }
+
+        #[test]
+        fn fallback_empty_section() {
+            assert_eq!(
+                extract_rust_code(
+                    "=={{header|Rust}}==\n{{omit from|Rust}}\n=={{header|Scala}}==\n"
+                ),
+                None
+            );
+        }
+
+        #[test]
+        fn variants_under_subheaders() {
+            let content = "=={{header|Rust}}==\n\
+                           <lang rust>// shared</lang>\n\
+                           ===Version 1===\n<lang rust>fn one() {}</lang>\n\
+                           === Version 2 ===\n<lang rust>fn two() {}</lang>\n\
+                           <lang rust>fn main() {}</lang>\n\
+                           =={{header|Scala}}==\n<lang rust>not rust</lang>\n";
+
+            assert_eq!(
+                extract_rust_variants(content),
+                vec![
+                    (String::from("#1"), String::from("// shared")),
+                    (String::from("Version 1"), String::from("fn one() {}")),
+                    (String::from("Version 2"), String::from("fn two() {}")),
+                    (String::from("Version 2 (2)"), String::from("fn main() {}")),
+                ]
+            );
+        }
+
+        #[test]
+        fn variants_without_subheaders() {
+            let content = "=={{header|Rust}}==\n\
+                           <lang rust>fn a() {}</lang>\n<lang rust>fn b() {}</lang>\n";
+
+            assert_eq!(
+                extract_rust_variants(content),
+                vec![
+                    (String::from("#1"), String::from("fn a() {}")),
+                    (String::from("#2"), String::from("fn b() {}")),
+                ]
+            );
+            assert!(extract_rust_variants("=={{header|Python}}==\n").is_empty());
+        }
+
+        #[test]
+        fn dedup_near_identical_variants() {
+            let content = "=={{header|Rust}}==\n\
+                           <lang rust>use std::io;\n\nfn main() {\n    run();\n}\n</lang>\n\
+                           <lang rust>use std::io;\n\nfn main() {\n    run(); // Copy\n}\n</lang>\n\
+                           <lang rust>fn other() {}</lang>\n";
+
+            let variants = dedup_variants(extract_rust_variants(content), 0.7);
+            let names = variants
+                .iter()
+                .map(|&(ref name, _)| name.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(names, vec!["#1", "#3"]);
+
+            assert_eq!(dedup_variants(extract_rust_variants(content), 1.0).len(), 3);
+        }
+
+        #[test]
+        fn no_rust_section() {
+            assert_eq!(
+                extract_rust_code("=={{header|Python}}==\n<lang python>pass</lang>\n"),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn languages() {
+        use super::languages;
+
+        let wiki_text = "{{task}}\n\
+                         =={{header|Ada}}==\n<lang ada>null;</lang>\n\
+                         == {{ header | Rust }} ==\n<lang rust>fn main() {}</lang>\n\
+                         ==={{header|Rust}}===\nSubsection\n\
+                         =={{header|rust}}==\nAnother solution\n\
+                         =={{Header|C sharp|C#}}==\n\
+                         =={{header|Go}} (goroutines)==\n";
+
+        assert_eq!(languages(wiki_text), vec!["Ada", "Rust", "C sharp", "Go"]);
+        assert!(languages("{{task}}").is_empty());
+    }
+
+    #[test]
+    fn rust_section() {
+        use super::rust_section;
+
+        let wiki_text = "{{task}}\n\
+                         ==Description==\n\
+                         =={{header|Ada}}==\n\
+                         ===Recursive===\n\
+                         =={{header|Rust}}==\n<lang rust>fn main() {}</lang>\n";
+
+        assert_eq!(rust_section(wiki_text), Some(4));
+        assert_eq!(rust_section("=={{header|Ada}}==\n"), None);
+    }
+
+    #[test]
+    fn revision_code() {
+        use std::collections::BTreeMap;
+
+        use serde_json;
+
+        use super::revision_code;
+
+        let code = revision_code(&[7, 8], |request| {
+            assert!(request
+                .query_pairs()
+                .any(|(k, v)| k == "revids" && v == "7|8"));
+
+            Ok(serde_json::from_str(
+                r#"{
+                    "query": {
+                        "pages": {
+                            "1": {
+                                "title": "100 doors",
+                                "revisions": [
+                                    { "*": "=={{header|Rust}}==\n<lang rust>old()</lang>\n" }
+                                ]
+                            },
+                            "2": {
+                                "title": "A+B",
+                                "revisions": [{ "*": "=={{header|Ada}}==\n" }]
+                            }
+                        }
+                    }
+                }"#,
+            )?)
+        }).unwrap();
+
+        let mut expected = BTreeMap::new();
+        expected.insert(String::from("100 doors"), Some(String::from("old()")));
+        expected.insert(String::from("A+B"), None);
+        assert_eq!(code, expected);
+    }
+
+    #[test]
+    fn redirect_targets() {
+        use std::collections::BTreeMap;
+
+        use serde_json;
+
+        use super::redirect_targets;
+
+        let titles = vec![
+            String::from("Old task"),
+            String::from("older_task"),
+            String::from("100 doors"),
+        ];
+
+        let targets = redirect_targets(&titles, |request| {
+            assert!(request.query_pairs().any(|(k, v)| k == "redirects" && v == "1"));
+
+            Ok(serde_json::from_str(
+                r#"{
+                    "query": {
+                        "normalized": [{ "from": "older_task", "to": "Older task" }],
+                        "redirects": [
+                            { "from": "Old task", "to": "New task" },
+                            { "from": "Older task", "to": "Old task" }
+                        ],
+                        "pages": {}
+                    }
+                }"#,
+            )?)
+        }).unwrap();
+
+        let mut expected = BTreeMap::new();
+        expected.insert(String::from("Old task"), String::from("New task"));
+        expected.insert(String::from("older_task"), String::from("New task"));
+        assert_eq!(targets, expected);
+    }
+
+    #[test]
+    fn unparseable() {
+        use serde_json;
+
+        use super::{parse_tasks, Response};
+
+        let response: Response = serde_json::from_str(
+            r#"{
+                "query": {
+                    "pages": {
+                        "1": {
+                            "title": "Parsed",
+                            "revisions": [
+                                { "*": "=={{header|Rust}}==\n<lang rust>fn main() {}</lang>\n" }
+                            ]
+                        },
+                        "2": {
+                            "title": "Unparseable",
+                            "revisions": [{ "*": "=={{header|Rust}}==\n{{works with|Rust}}\n" }]
+                        },
+                        "3": {
+                            "title": "Unimplemented",
+                            "revisions": [{ "*": "=={{header|Ada}}==\n<lang ada>null;</lang>\n" }]
+                        }
+                    }
+                }
+            }"#,
+        ).unwrap();
+
+        let mut tasks = parse_tasks(&response);
+        tasks.sort_by_key(|task| task.title());
+        let unparseable = tasks
+            .iter()
+            .map(|task| (task.title(), task.has_rust_section(), task.is_unparseable()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            unparseable,
+            vec![
+                (String::from("Parsed"), true, false),
+                (String::from("Unimplemented"), false, false),
+                (String::from("Unparseable"), true, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn description() {
+        use super::description;
+
+        let wiki_text = "{{task|Basic language learning}}[[Category:Simple]]\n\
+                         Write a program that prints '''Hello, world!''' to the\n\
+                         [[standard output|console]].{{omit from|Bash}}\n\
+                         \n\
+                         ==Related tasks==\n\
+                         * [[Hello world/Text]] <br>\n\
+                         =={{header|Ada}}==\nAda prose\n\
+                         =={{header|Rust}}==\n<lang rust>fn main() {}</lang>\n";
+
+        assert_eq!(
+            description(wiki_text),
+            Some(String::from(
+                "Write a program that prints Hello, world! to the console.\n\n\
+                 * Hello world/Text"
+            ))
+        );
+        assert_eq!(description("{{task}}\n=={{header|Rust}}==\n"), None);
+    }
+
+    #[test]
+    fn normalize_title() {
+        use super::normalize_title;
+
+        assert_eq!(normalize_title("hello world"), "Hello world");
+        assert_eq!(normalize_title("Hello world"), "Hello world");
+        assert_eq!(normalize_title("élan"), "Élan");
+        assert_eq!(normalize_title(""), "");
+    }
+
     #[test]
     fn encode_decode() {
         const TITLE: &str = "Penney's game";
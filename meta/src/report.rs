@@ -0,0 +1,250 @@
+//! The schema of the JSON reports written by the `coverage` binary.
+//!
+//! These types are only used to read reports back, so that consumers can check that a report
+//! matches the schema that the current version of the binary writes.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_json::{self, Value};
+
+use DiffKind;
+
+/// A report of the status of a set of tasks.
+///
+/// Depending on the options it was written with, a report is either a plain array of tasks, or an
+/// object with the tasks and a summary of their statuses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageReport {
+    /// The tasks in the report.
+    pub tasks: Vec<ReportTask>,
+
+    /// The summary of the tasks, if the report includes one.
+    pub summary: Option<ReportSummary>,
+}
+
+/// The entry of a single task in a report.
+///
+/// The fields that are included can be selected with `--json-fields`, so any of them may be
+/// missing. Fields that are not part of the schema are rejected.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReportTask {
+    /// The title of the task.
+    #[serde(default)]
+    pub title: Option<String>,
+
+    /// The URL of the task on the wiki.
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// The code of the local implementation.
+    #[serde(default)]
+    pub local_code: Option<String>,
+
+    /// The code of the implementation on the wiki.
+    #[serde(default)]
+    pub remote_code: Option<String>,
+
+    /// The path to the local implementation.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+
+    /// How the local and remote implementations differ.
+    #[serde(default)]
+    pub diff_kind: Option<DiffKind>,
+
+    /// How similar the local and remote implementations are.
+    #[serde(default)]
+    pub diff_ratio: Option<f64>,
+
+    /// The checksum of the local implementation. See `checksum`.
+    #[serde(default)]
+    pub local_checksum: Option<String>,
+
+    /// The checksum of the implementation on the wiki. See `checksum`.
+    #[serde(default)]
+    pub remote_checksum: Option<String>,
+
+    /// The size of the local implementation in bytes.
+    #[serde(default)]
+    pub local_size_bytes: Option<usize>,
+
+    /// The size of the implementation on the wiki in bytes.
+    #[serde(default)]
+    pub remote_size_bytes: Option<usize>,
+
+    /// The size of the wiki page of the task in bytes.
+    #[serde(default)]
+    pub remote_page_size_bytes: Option<usize>,
+
+    /// The description of the task on the wiki.
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// The summary of a report. See `Summary` for the meaning of each field.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(missing_docs)]
+pub struct ReportSummary {
+    pub total: usize,
+    pub implemented: usize,
+    pub both: usize,
+    pub local_only: usize,
+    pub remote_only: usize,
+    pub unimplemented: usize,
+    pub coverage_percent: f64,
+
+    // Reports written before these totals were added don't include them.
+    #[serde(default)]
+    pub differing: usize,
+    #[serde(default)]
+    pub lines_added: usize,
+    #[serde(default)]
+    pub lines_removed: usize,
+}
+
+/// A report with a summary.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SummarizedReport {
+    tasks: Vec<ReportTask>,
+    summary: ReportSummary,
+}
+
+impl CoverageReport {
+    /// Parses a report, returning an error that describes the first incompatibility with the
+    /// schema.
+    pub fn from_json(json: &str) -> Result<CoverageReport, serde_json::Error> {
+        let value = serde_json::from_str::<Value>(json)?;
+
+        // Both shapes are decoded separately instead of with an untagged enum, because the errors
+        // of untagged enums don't say what is wrong.
+        if value.is_array() {
+            Ok(CoverageReport {
+                tasks: serde_json::from_value(value)?,
+                summary: None,
+            })
+        } else {
+            let report = serde_json::from_value::<SummarizedReport>(value)?;
+            Ok(CoverageReport {
+                tasks: report.tasks,
+                summary: Some(report.summary),
+            })
+        }
+    }
+}
+
+/// True if the file should be compressed with gzip, because its name ends in `.gz`.
+fn is_compressed(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "gz")
+}
+
+/// Writes a report to a file. If the name of the file ends in `.gz`, the report is compressed
+/// with gzip, which makes reports with the code of every task much smaller.
+pub fn write_report<P: AsRef<Path>>(path: P, json: &str) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut file = File::create(path)?;
+
+    if is_compressed(path) {
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(json.as_bytes())?;
+        encoder.finish()?;
+    } else {
+        file.write_all(json.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Reads a report written by `write_report`, decompressing it if the name of the file ends in
+/// `.gz`.
+pub fn read_report<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let path = path.as_ref();
+    let mut file = File::open(path)?;
+
+    let mut json = String::new();
+    if is_compressed(path) {
+        GzDecoder::new(file).read_to_string(&mut json)?;
+    } else {
+        file.read_to_string(&mut json)?;
+    }
+
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{read_report, write_report, CoverageReport};
+    use temp_dir::TempDir;
+    use DiffKind;
+
+    #[test]
+    fn compressed_round_trip() {
+        let temp_dir = TempDir::new("rosetta-meta-compressed-report");
+        let dir = temp_dir.path();
+
+        let json = r#"[{ "title": "100 doors", "remote_code": "fn main() {}" }]"#;
+
+        for name in &["report.json", "report.json.gz"] {
+            let path = dir.join(name);
+            write_report(&path, json).unwrap();
+            assert_eq!(read_report(&path).unwrap(), json);
+        }
+
+        // The compressed report really is compressed.
+        let compressed = fs::read(dir.join("report.json.gz")).unwrap();
+        assert_eq!(&compressed[..2], &[0x1f, 0x8b]);
+    }
+
+    #[test]
+    fn task_array() {
+        let report = CoverageReport::from_json(
+            r#"[{ "title": "100 doors", "diff_kind": "whitespace_only", "local_code": null }]"#,
+        ).unwrap();
+
+        assert_eq!(report.tasks.len(), 1);
+        assert_eq!(report.tasks[0].title, Some(String::from("100 doors")));
+        assert_eq!(report.tasks[0].diff_kind, Some(DiffKind::WhitespaceOnly));
+        assert_eq!(report.summary, None);
+    }
+
+    #[test]
+    fn summarized() {
+        let report = CoverageReport::from_json(
+            r#"{
+                "tasks": [],
+                "summary": {
+                    "total": 2,
+                    "implemented": 1,
+                    "both": 1,
+                    "local_only": 0,
+                    "remote_only": 1,
+                    "unimplemented": 0,
+                    "coverage_percent": 50.0
+                }
+            }"#,
+        ).unwrap();
+
+        assert_eq!(report.summary.unwrap().coverage_percent, 50.0);
+    }
+
+    #[test]
+    fn incompatible() {
+        let unknown_field = CoverageReport::from_json(r#"[{ "name": "100 doors" }]"#);
+        assert!(unknown_field.unwrap_err().to_string().contains("name"));
+
+        let wrong_type = CoverageReport::from_json(r#"[{ "local_size_bytes": "12" }]"#);
+        assert!(wrong_type.is_err());
+
+        let missing_summary = CoverageReport::from_json(r#"{ "tasks": [] }"#);
+        assert!(missing_summary.unwrap_err().to_string().contains("summary"));
+    }
+}
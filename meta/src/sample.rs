@@ -0,0 +1,105 @@
+//! Selecting a representative subset of tasks.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use rand::{ChaChaRng, Rng, SeedableRng};
+
+/// How `sample_per_category` picks the tasks of each category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampling {
+    /// Pick the tasks whose titles come first alphabetically.
+    Alphabetical,
+
+    /// Pick tasks at random. The same seed always picks the same tasks.
+    Random {
+        /// The seed of the random number generator.
+        seed: u64,
+    },
+}
+
+/// Picks at most `max` tasks from each category, and returns the titles of all tasks that were
+/// picked.
+///
+/// `categories` maps the name of each category to the titles of its tasks. A task that belongs to
+/// several categories may be picked by any of them.
+pub fn sample_per_category(
+    categories: &BTreeMap<String, Vec<String>>,
+    max: usize,
+    sampling: Sampling,
+) -> BTreeSet<String> {
+    let mut rng = match sampling {
+        Sampling::Random { seed } => Some(ChaChaRng::from_seed(
+            &[seed as u32, (seed >> 32) as u32][..],
+        )),
+        Sampling::Alphabetical => None,
+    };
+
+    let mut sample = BTreeSet::new();
+
+    // Categories are visited in a fixed order, so the random number generator produces the same
+    // picks for the same input.
+    for titles in categories.values() {
+        let mut titles = titles.iter().collect::<Vec<_>>();
+        titles.sort();
+
+        if let Some(ref mut rng) = rng {
+            rng.shuffle(&mut titles);
+        }
+
+        sample.extend(titles.into_iter().take(max).cloned());
+    }
+
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{sample_per_category, Sampling};
+
+    fn categories() -> BTreeMap<String, Vec<String>> {
+        let mut categories = BTreeMap::new();
+        categories.insert(
+            String::from("Sorting"),
+            vec!["Quicksort", "Bubble sort", "Merge sort", "Heapsort"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        );
+        categories.insert(
+            String::from("Recursion"),
+            vec!["Quicksort", "Ackermann function"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        );
+        categories
+    }
+
+    #[test]
+    fn alphabetical() {
+        let sample = sample_per_category(&categories(), 1, Sampling::Alphabetical);
+
+        assert_eq!(
+            sample.into_iter().collect::<Vec<_>>(),
+            vec!["Ackermann function", "Bubble sort"]
+        );
+    }
+
+    #[test]
+    fn random_is_deterministic() {
+        let first = sample_per_category(&categories(), 2, Sampling::Random { seed: 42 });
+        let second = sample_per_category(&categories(), 2, Sampling::Random { seed: 42 });
+
+        assert_eq!(first, second);
+        assert!(first.len() >= 2 && first.len() <= 4);
+    }
+
+    #[test]
+    fn fewer_tasks_than_max() {
+        let sample = sample_per_category(&categories(), 10, Sampling::Random { seed: 1 });
+
+        assert_eq!(sample.len(), 5);
+    }
+}
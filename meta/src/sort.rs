@@ -0,0 +1,122 @@
+//! Sorting task titles in an order that reads naturally.
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// The orders that task titles can be sorted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Sort titles alphabetically, ignoring case and accents, so that `Élan` comes right after
+    /// `elan`. The rules are fixed, so the order doesn't depend on the locale of the system.
+    Alpha,
+
+    /// Sort titles by their bytes, so that all accented titles come after the unaccented ones.
+    Bytes,
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        SortOrder::Alpha
+    }
+}
+
+impl SortOrder {
+    /// The names of the orders, as accepted by `from_str`.
+    pub const VALUES: &'static [&'static str] = &["alpha", "bytes"];
+
+    /// Compares two titles in this order.
+    pub fn compare(self, a: &str, b: &str) -> Ordering {
+        match self {
+            SortOrder::Alpha => compare_titles(a, b),
+            SortOrder::Bytes => a.cmp(b),
+        }
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SortOrder, String> {
+        match s {
+            "alpha" => Ok(SortOrder::Alpha),
+            "bytes" => Ok(SortOrder::Bytes),
+            _ => Err(format!("unknown sort order '{}'", s)),
+        }
+    }
+}
+
+/// Compares two titles alphabetically.
+///
+/// Titles are first compared without case and accents. Ties are broken by comparing the accented
+/// letters, which come after their base letters, and then by case, so that the order is total.
+pub fn compare_titles(a: &str, b: &str) -> Ordering {
+    let primary = |title: &str| {
+        title
+            .chars()
+            .map(fold_diacritic)
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+
+    primary(a)
+        .cmp(&primary(b))
+        .then_with(|| a.to_lowercase().cmp(&b.to_lowercase()))
+        .then_with(|| a.cmp(b))
+}
+
+/// Replaces common accented Latin characters with their unaccented equivalents.
+pub fn fold_diacritic(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' => 'A',
+        'ç' => 'c',
+        'Ç' => 'C',
+        'è' | 'é' | 'ê' | 'ë' => 'e',
+        'È' | 'É' | 'Ê' | 'Ë' => 'E',
+        'ì' | 'í' | 'î' | 'ï' => 'i',
+        'Ì' | 'Í' | 'Î' | 'Ï' => 'I',
+        'ñ' => 'n',
+        'Ñ' => 'N',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => 'o',
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' => 'O',
+        'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'Ù' | 'Ú' | 'Û' | 'Ü' => 'U',
+        'ý' | 'ÿ' => 'y',
+        'Ý' => 'Y',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare_titles, SortOrder};
+
+    fn sorted<'a>(titles: &[&'a str], order: SortOrder) -> Vec<&'a str> {
+        let mut titles = titles.to_vec();
+        titles.sort_by(|a, b| order.compare(a, b));
+        titles
+    }
+
+    #[test]
+    fn accented_titles() {
+        let titles = ["Zeta", "Éta", "Eta", "eta", "Euler", "Abc"];
+
+        assert_eq!(
+            sorted(&titles, SortOrder::Alpha),
+            vec!["Abc", "Eta", "eta", "Éta", "Euler", "Zeta"]
+        );
+        assert_eq!(
+            sorted(&titles, SortOrder::Bytes),
+            vec!["Abc", "Eta", "Euler", "Zeta", "eta", "Éta"]
+        );
+    }
+
+    #[test]
+    fn total_order() {
+        use std::cmp::Ordering;
+
+        assert_eq!(compare_titles("a", "a"), Ordering::Equal);
+        assert_eq!(compare_titles("A", "a"), Ordering::Less);
+        assert_eq!(compare_titles("ä", "b"), Ordering::Less);
+    }
+}
@@ -0,0 +1,146 @@
+//! Aggregate statistics over a set of tasks.
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use diff::{self, Change};
+use {DiffKind, Task};
+
+/// Counts of tasks by implementation status.
+///
+/// A task is considered *implemented* if and only if it has code in the local repository,
+/// regardless of whether it is implemented on the wiki.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Summary {
+    /// The number of tasks that were counted.
+    pub total: usize,
+
+    /// The number of tasks that are implemented in the local repository.
+    pub implemented: usize,
+
+    /// The number of tasks that are implemented both locally and on the wiki.
+    pub both: usize,
+
+    /// The number of tasks that are only implemented locally.
+    pub local_only: usize,
+
+    /// The number of tasks that are only implemented on the wiki.
+    pub remote_only: usize,
+
+    /// The number of tasks that are implemented neither locally nor on the wiki.
+    pub unimplemented: usize,
+
+    /// The number of tasks whose local and remote code differ substantively.
+    pub differing: usize,
+
+    /// The number of lines that the local code of the differing tasks adds, compared to the
+    /// code on the wiki.
+    pub lines_added: usize,
+
+    /// The number of lines that the local code of the differing tasks removes, compared to the
+    /// code on the wiki.
+    pub lines_removed: usize,
+}
+
+impl Summary {
+    /// Creates an empty summary.
+    pub fn new() -> Summary {
+        Default::default()
+    }
+
+    /// Adds a task to the counts.
+    ///
+    /// The lines that a differing task changes are not counted here, because comparing the code
+    /// is slow. They are added with `add_line_changes`, usually from the diff that was printed.
+    pub fn add(&mut self, task: &Task) {
+        let local = task.local.is_some();
+        let remote = task.remote.source().is_some();
+
+        self.total += 1;
+
+        if local {
+            self.implemented += 1;
+        }
+
+        match (local, remote) {
+            (true, true) => self.both += 1,
+            (true, false) => self.local_only += 1,
+            (false, true) => self.remote_only += 1,
+            (false, false) => self.unimplemented += 1,
+        }
+
+        if task.diff_kind() == DiffKind::Substantive {
+            self.differing += 1;
+        }
+    }
+
+    /// Adds the lines that the local code of a differing task adds and removes, compared to the
+    /// code on the wiki, to the totals.
+    pub fn add_line_changes(&mut self, changes: &[Change]) {
+        let (added, removed) = diff::line_changes(changes);
+        self.lines_added += added;
+        self.lines_removed += removed;
+    }
+
+    /// The percentage of counted tasks that are implemented locally, rounded to two decimal
+    /// places. If no tasks were counted, returns `0.0`.
+    pub fn coverage_percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+
+        let percent = self.implemented as f64 / self.total as f64 * 100.0;
+        (percent * 100.0).round() / 100.0
+    }
+}
+
+impl Serialize for Summary {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Summary", 10)?;
+        state.serialize_field("total", &self.total)?;
+        state.serialize_field("implemented", &self.implemented)?;
+        state.serialize_field("both", &self.both)?;
+        state.serialize_field("local_only", &self.local_only)?;
+        state.serialize_field("remote_only", &self.remote_only)?;
+        state.serialize_field("unimplemented", &self.unimplemented)?;
+        state.serialize_field("coverage_percent", &self.coverage_percent())?;
+        state.serialize_field("differing", &self.differing)?;
+        state.serialize_field("lines_added", &self.lines_added)?;
+        state.serialize_field("lines_removed", &self.lines_removed)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Summary;
+    use diff;
+
+    #[test]
+    fn coverage_percent() {
+        let summary = Summary {
+            total: 3,
+            implemented: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(summary.coverage_percent(), 66.67);
+    }
+
+    #[test]
+    fn empty_coverage_percent() {
+        assert_eq!(Summary::new().coverage_percent(), 0.0);
+    }
+
+    #[test]
+    fn line_changes() {
+        let mut summary = Summary::new();
+        summary.add_line_changes(&diff::changeset("a\nb\nc", "a\nB\nc\nd"));
+        summary.add_line_changes(&diff::changeset("a", ""));
+
+        assert_eq!(summary.lines_added, 2);
+        assert_eq!(summary.lines_removed, 2);
+    }
+}
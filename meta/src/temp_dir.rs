@@ -0,0 +1,43 @@
+//! Temporary directories for the tests of this crate.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A new directory in the temporary directory of the system, which is removed when it is dropped,
+/// even if the test that uses it fails.
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Creates an empty directory whose name starts with `prefix`. The rest of the name is unique,
+    /// so that tests that run at the same time, even in other processes, never share a directory.
+    pub fn new(prefix: &str) -> TempDir {
+        static COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs() * 1_000_000_000 + u64::from(time.subsec_nanos()))
+            .unwrap_or(0);
+        let count = COUNT.fetch_add(1, Ordering::SeqCst);
+
+        let path = env::temp_dir().join(format!("{}-{:x}-{}", prefix, nanos, count));
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
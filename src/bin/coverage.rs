@@ -3,20 +3,42 @@ extern crate clap;
 #[macro_use]
 extern crate serde_json;
 
-extern crate difference;
+extern crate atty;
+extern crate ctrlc;
+extern crate env_logger;
+extern crate log;
 extern crate meta;
+extern crate notify;
+extern crate reqwest;
 extern crate serde;
 extern crate term;
+extern crate term_size;
+extern crate webbrowser;
 
-use std::fs::File;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs::{self, File, OpenOptions};
 use std::io;
 use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::rc::Rc;
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
+use std::usize;
 
-use clap::{App, Arg};
-use difference::{Changeset, Difference};
-use term::Terminal;
+use clap::{App, Arg, SubCommand};
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
+use reqwest::Url;
 
-use meta::{Task, TaskIndex};
+use meta::audit::{self, Audit, Rename};
+use meta::local::{self, LocalTask, NamingMismatch, TodoMarker};
+use meta::report;
+use meta::sample::{self, Sampling};
+use meta::{
+    diff, slugify, CachingClient, Change, CoverageReport, DiffKind, Error, FetchErrorKind,
+    HttpClient, SortOrder, Summary, Task, TaskIndex, TaskPresence, UrlStyle, WikiClient,
+};
 
 const ABOUT: &str = r#"
 Query differences between the rust-rosetta repository and the Rosetta Code wiki.
@@ -30,9 +52,10 @@ arg_enum!{
     #[derive(Debug)]
     enum Filter {
         All,
-        LocalOnly,
-        RemoteOnly,
-        Unimplemented
+        Local,
+        Remote,
+        Unimplemented,
+        Diff
     }
 }
 
@@ -42,31 +65,418 @@ impl Default for Filter {
     }
 }
 
+/// The values accepted by `--filter`.
+const FILTERS: &[&str] = &["all", "local", "remote", "unimplemented", "diff"];
+
+/// The options that need the code or other details of each task, so that the tasks can't be
+/// checked with `TaskIndex::fetch_status` alone. Options that are not listed must work with the
+/// presence-only listing.
+const DETAIL_OPTIONS: &[&str] = &[
+    "diff",
+    "classify",
+    "detect-moves",
+    "no-diff-context-for-identical",
+    "streaming-diff",
+    "max-diffs",
+    "ignore-whitespace",
+    "show-sizes",
+    "show-languages",
+    "show-edit-url",
+    "include-description",
+    "url-style",
+    "missing-deps",
+    "check-main",
+    "check-naming",
+    "todos",
+    "fail-on-match",
+    "suggest-renames",
+    "list-values",
+    "min-languages",
+    "since-revision",
+    "save-revisions",
+    "snapshot",
+    "revisions",
+    "min-ratio",
+    "max-ratio",
+    "only-changed-checksums",
+    "json-file",
+    "json-compact",
+    "relative-paths",
+    "json-fields",
+    "columns",
+    "emit-patches",
+    "dump-remote",
+    "overwrite",
+    "heatmap",
+    "group-by",
+    "summary",
+    "fail-under",
+    "require-remote",
+    "fail-on-parse-error",
+    "deadline",
+    "watch",
+];
+
+/// The values accepted by `--diff-against`.
+const DIFF_AGAINST: &[&str] = &["latest", "snapshot", "revision"];
+
+/// The values accepted by `--format`.
+const FORMATS: &[&str] = &["full", "oneline"];
+
+/// The values accepted by `--group-by`.
+const GROUPINGS: &[&str] = &["status"];
+
+/// The validations run by the `check` subcommand, in the order they are run.
+const CHECKS: &[&str] = &["build", "fmt", "links", "orphans", "naming", "parse-errors"];
+
+/// Describes the checks of the `check` subcommand.
+const CHECKS_HELP: &str = "CHECKS:
+    build           `cargo check --all` succeeds
+    fmt             `cargo fmt --all` does not change any code
+    links           every local task is still listed on the wiki
+    orphans         every crate in the tasks directory is a member of the workspace
+    naming          every task is in the directory derived from its title
+    parse-errors    the Rust code of every local task can be extracted from the wiki";
+
+/// Returns the values accepted by an option, for `--list-values`.
+fn option_values(option: &str) -> &'static [&'static str] {
+    match option {
+        "filter" => FILTERS,
+        "format" => FORMATS,
+        "group-by" => GROUPINGS,
+        "columns" => COLUMNS,
+        "json-fields" => JSON_FIELDS,
+        "sort" => SortOrder::VALUES,
+        "url-style" => UrlStyle::VALUES,
+        _ => unreachable!("no values for option '{}'", option),
+    }
+}
+
+/// Where the output is written. This is usually a terminal, but if `term` doesn't support the
+/// terminal, or there is none, plain text is written to stdout instead.
+trait Console: Write {
+    /// Sets the color of the text written next.
+    fn fg(&mut self, color: term::color::Color) -> io::Result<()>;
+
+    /// Sets an attribute of the text written next, such as bold.
+    fn attr(&mut self, attr: term::Attr) -> io::Result<()>;
+
+    /// Resets the color and attributes of the text.
+    fn reset(&mut self) -> io::Result<()>;
+}
+
+impl Console for Box<term::StdoutTerminal> {
+    fn fg(&mut self, color: term::color::Color) -> io::Result<()> {
+        term::Terminal::fg(&mut **self, color)
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    fn attr(&mut self, attr: term::Attr) -> io::Result<()> {
+        term::Terminal::attr(&mut **self, attr)
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        term::Terminal::reset(&mut **self)
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+}
+
+/// Writes plain text to stdout, ignoring colors and attributes.
+struct PlainConsole(io::Stdout);
+
+impl Write for PlainConsole {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl Console for PlainConsole {
+    fn fg(&mut self, _: term::color::Color) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn attr(&mut self, _: term::Attr) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the terminal on stdout, falling back to plain text if it isn't supported or if `color`
+/// is false.
+fn stdout_console(color: bool) -> Box<Console> {
+    if color {
+        if let Some(t) = term::stdout() {
+            return Box::new(t);
+        }
+    }
+
+    Box::new(PlainConsole(io::stdout()))
+}
+
+/// Options controlling how each task is printed to the terminal.
+#[derive(Debug, Default, Clone)]
+struct PrintOptions {
+    /// Print a diff between the local and remote code.
+    diff: bool,
+
+    /// Print the size of the local and remote code.
+    sizes: bool,
+
+    /// Print the URL that edits the Rust section of each task on the wiki.
+    edit_url: bool,
+
+    /// Print the languages that each task is implemented in on the wiki.
+    languages: bool,
+
+    /// Print the beginning of the description of each task on the wiki.
+    description: bool,
+
+    /// Annotate each hunk of a diff with the kind of construct that it changes.
+    classify: bool,
+
+    /// Print blocks of lines that were moved as moves rather than as removals and additions.
+    detect_moves: bool,
+
+    /// Print a single line instead of a diff for tasks whose code is identical.
+    skip_identical_diffs: bool,
+
+    /// Don't report differences that only consist of whitespace, such as trailing whitespace or
+    /// indentation.
+    ignore_whitespace: bool,
+
+    /// Print the URL of the task in the given style.
+    url_style: Option<UrlStyle>,
+
+    /// Wrap lines that are wider than this many columns. If `None`, lines are never wrapped.
+    wrap_width: Option<usize>,
+
+    /// The number of columns between tab stops, used when expanding tabs.
+    tab_width: usize,
+}
+
+/// Replaces each tab in a line with enough spaces to reach the next tab stop.
+fn expand_tabs(line: &str, tab_width: usize) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = if tab_width == 0 {
+                0
+            } else {
+                tab_width - column % tab_width
+            };
+            expanded.extend((0..spaces).map(|_| ' '));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+
+    expanded
+}
+
+/// Splits a line into pieces that are at most `width` columns wide, after expanding tabs.
+///
+/// Lines are broken after whitespace where possible. Every piece except the first is indented to
+/// make it clear that it continues the previous one.
+fn wrap(line: &str, width: Option<usize>, tab_width: usize) -> Vec<String> {
+    const CONTINUATION_INDENT: &str = "  ";
+
+    let line = expand_tabs(line, tab_width);
+
+    let width = match width {
+        Some(width) if width > CONTINUATION_INDENT.len() => width,
+        _ => return vec![line],
+    };
+
+    let mut pieces = vec![];
+    let mut rest = line.as_str();
+
+    loop {
+        let (indent, available) = if pieces.is_empty() {
+            ("", width)
+        } else {
+            (CONTINUATION_INDENT, width - CONTINUATION_INDENT.len())
+        };
+
+        let split = match rest.char_indices().nth(available) {
+            Some((split, _)) => split,
+            None => {
+                pieces.push(format!("{}{}", indent, rest));
+                break;
+            }
+        };
+
+        let split = match rest[..split].rfind(char::is_whitespace) {
+            Some(space) if space > 0 => space + 1,
+            _ => split,
+        };
+
+        pieces.push(format!("{}{}", indent, &rest[..split]));
+        rest = &rest[split..];
+    }
+
+    pieces
+}
+
+/// Writes a line to the terminal, wrapping it according to the options. Each piece of the line is
+/// preceded by `prefix`.
+fn write_wrapped<T: ?Sized>(
+    t: &mut T,
+    prefix: &str,
+    line: &str,
+    options: &PrintOptions,
+) -> io::Result<()>
+where
+    T: Console,
+{
+    let width = options
+        .wrap_width
+        .map(|width| width.saturating_sub(prefix.len()));
+
+    for piece in wrap(line, width, options.tab_width) {
+        writeln!(t, "{}{}", prefix, piece)?;
+    }
+
+    Ok(())
+}
+
+/// True if a task should be included in the output of the program.
+fn matches_filter(task: &Task, filter: &Filter, ignore_whitespace: bool) -> bool {
+    match *filter {
+        Filter::All => true,
+        Filter::Local => task.is_local_only(),
+        Filter::Remote => task.is_remote_only(),
+        Filter::Unimplemented => task.is_unimplemented(),
+        Filter::Diff => is_diverged(task, ignore_whitespace),
+    }
+}
+
+/// True if the local and remote code of a task differ, optionally ignoring differences in
+/// whitespace, whether trailing or in the indentation.
+fn is_diverged(task: &Task, ignore_whitespace: bool) -> bool {
+    if ignore_whitespace {
+        task.diff_kind() == DiffKind::Substantive
+    } else {
+        task.is_diverged()
+    }
+}
+
+/// The minimum number of non-blank lines that a block must have to be reported as moved. Smaller
+/// blocks, such as lone closing braces, match each other all the time.
+const MIN_MOVED_LINES: usize = 2;
+
+/// Finds the removed blocks that were added back elsewhere, and the blocks that they were added
+/// as. Returns whether each difference is part of such a move.
+///
+/// Blocks are compared line by line, ignoring indentation, since moved code is often nested at a
+/// different depth.
+fn find_moves(changes: &[Change]) -> Vec<bool> {
+    fn normalize(lines: &[String]) -> Vec<&str> {
+        lines.iter().map(|line| line.trim()).collect()
+    }
+
+    let mut moved = vec![false; changes.len()];
+
+    for (i, removed) in changes.iter().enumerate() {
+        let removed = match *removed {
+            Change::Remove(ref lines) => normalize(lines),
+            _ => continue,
+        };
+
+        if removed.iter().filter(|line| !line.is_empty()).count() < MIN_MOVED_LINES {
+            continue;
+        }
+
+        let destination = changes
+            .iter()
+            .enumerate()
+            .position(|(j, added)| match *added {
+                Change::Add(ref lines) => !moved[j] && normalize(lines) == removed,
+                _ => false,
+            });
+
+        if let Some(j) = destination {
+            moved[i] = true;
+            moved[j] = true;
+        }
+    }
+
+    moved
+}
+
 /// Prints a colored diff of two strings to the terminal.
-fn print_diff<T: ?Sized>(t: &mut T, s1: &str, s2: &str) -> io::Result<()>
+fn print_diff<T: ?Sized>(t: &mut T, changes: &[Change], options: &PrintOptions) -> io::Result<()>
 where
-    T: Terminal,
+    T: Console,
 {
-    let changeset = Changeset::new(s1, s2, "\n");
+    let moved = if options.detect_moves {
+        find_moves(changes)
+    } else {
+        vec![false; changes.len()]
+    };
+
+    for (i, change) in changes.iter().enumerate() {
+        let starts_hunk = match (i.checked_sub(1).map(|i| &changes[i]), change) {
+            (_, &Change::Same(_)) => false,
+            (Some(&Change::Same(_)), _) | (None, _) => true,
+            _ => false,
+        };
+
+        if options.classify && starts_hunk {
+            let lines = changes[i..]
+                .iter()
+                .take_while(|change| match **change {
+                    Change::Same(_) => false,
+                    _ => true,
+                })
+                .flat_map(|change| change.lines())
+                .map(String::as_str);
 
-    for change in changeset.diffs {
-        match change {
-            Difference::Same(ref x) => {
+            t.fg(term::color::CYAN)?;
+            writeln!(t, "@@ {} @@", diff::classify_hunk(lines))?;
+        }
+
+        let prefix = match *change {
+            Change::Same(_) => {
                 t.reset()?;
-                writeln!(t, " {}", x)?;
+                " "
+            }
+            // Moved blocks are marked with `<` where they were removed, and `>` where they were
+            // added.
+            Change::Remove(_) if moved[i] => {
+                t.fg(term::color::BLUE)?;
+                "<"
+            }
+            Change::Add(_) if moved[i] => {
+                t.fg(term::color::BLUE)?;
+                ">"
             }
-            Difference::Add(ref x) => {
+            Change::Add(_) => {
                 t.fg(term::color::GREEN)?;
-                for line in x.split('\n') {
-                    writeln!(t, "+{}", line)?;
-                }
+                "+"
             }
-            Difference::Rem(ref x) => {
+            Change::Remove(_) => {
                 t.fg(term::color::RED)?;
-                for line in x.split('\n') {
-                    writeln!(t, "-{}", line)?;
-                }
+                "-"
             }
+        };
+
+        for line in change.lines() {
+            write_wrapped(t, prefix, line, options)?;
         }
     }
     t.reset()?;
@@ -74,127 +484,3652 @@ where
     Ok(())
 }
 
+/// Prints the line that replaces the diff of a task whose code is identical.
+fn print_identical<T: ?Sized>(t: &mut T) -> io::Result<()>
+where
+    T: Console,
+{
+    t.fg(term::color::GREEN)?;
+    writeln!(t, "Identical")?;
+    t.reset()?;
+    Ok(())
+}
+
 /// Prints a task in a human-readable format.
-fn print_task<T: ?Sized>(t: &mut T, task: &Task, diff: bool) -> io::Result<()>
+fn print_task<T: ?Sized>(t: &mut T, task: &Task, options: &PrintOptions) -> io::Result<()>
 where
-    T: Terminal,
+    T: Console,
 {
     t.attr(term::Attr::Bold)?;
-    writeln!(t, "{}", task.title())?;
+    write_wrapped(t, "", &task.title(), options)?;
     t.reset()?;
 
+    if let Some(style) = options.url_style {
+        writeln!(t, "{}", task.url_with_style(style))?;
+    }
+
+    if options.edit_url {
+        writeln!(t, "Edit: {}", task.edit_url())?;
+    }
+
     write!(t, "Local:")?;
     write_status(t, task.local_code().is_some())?;
 
     write!(t, "Remote:")?;
     write_status(t, task.remote_code().is_some())?;
+
+    if options.sizes {
+        write!(t, "Size:")?;
+        write_size(t, task.local_size_bytes())?;
+        write!(t, "/")?;
+        write_size(t, task.remote_size_bytes())?;
+        write!(t, " (page: {} bytes)", task.remote_page_size_bytes())?;
+    }
+
     writeln!(t, "")?;
 
-    if let (Some(ref local_code), Some(ref remote_code)) = (task.local_code(), task.remote_code()) {
-        if diff {
-            print_diff(t, remote_code, local_code)?;
+    if options.languages {
+        let languages = task.languages();
+        if languages.is_empty() {
+            writeln!(t, "Languages: none")?;
+        } else {
+            writeln!(t, "Languages: {}", languages.join(", "))?;
+        }
+    }
+
+    if options.description {
+        if let Some(description) = task.description() {
+            let description = truncate(&description, MAX_DESCRIPTION_CHARS);
+            writeln!(t, "Description: {}", description)?;
+        }
+    }
+
+    if options.diff {
+        match task.diff_kind() {
+            DiffKind::Missing => {}
+            DiffKind::Identical if options.skip_identical_diffs => {
+                print_identical(t)?;
+            }
+            DiffKind::WhitespaceOnly | DiffKind::IndentationOnly if options.ignore_whitespace => {
+                if options.skip_identical_diffs {
+                    print_identical(t)?;
+                }
+            }
+            DiffKind::WhitespaceOnly => {
+                t.fg(term::color::YELLOW)?;
+                writeln!(t, "Whitespace-only differences")?;
+                t.reset()?;
+            }
+            DiffKind::IndentationOnly => {
+                t.fg(term::color::YELLOW)?;
+                writeln!(t, "Indentation-only differences")?;
+                t.reset()?;
+            }
+            DiffKind::Identical | DiffKind::Substantive => {
+                print_diff(t, &task.changeset().unwrap(), options)?
+            }
         }
     }
 
     Ok(())
 }
 
-/// Writes a boolean as a pretty, human-readable string.
-fn write_status<T: ?Sized>(t: &mut T, boolean: bool) -> io::Result<()>
+/// Shortens text to at most `max_chars` characters, ending it with an ellipsis if it was cut.
+/// Line breaks are replaced with spaces, so the text fits on one line.
+fn truncate(text: &str, max_chars: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.chars().count() <= max_chars {
+        text
+    } else {
+        let mut truncated = text.chars().take(max_chars - 1).collect::<String>();
+        truncated.push('…');
+        truncated
+    }
+}
+
+/// Prints a diff between two pieces of code.
+fn print_code_diff<T: ?Sized>(
+    t: &mut T,
+    old: &str,
+    new: &str,
+    options: &PrintOptions,
+) -> io::Result<()>
+where
+    T: Console,
+{
+    print_diff(t, &diff::changeset(old, new), options)
+}
+
+/// Prints whether a task is implemented, like `print_task` does for a task without any details.
+fn print_presence<T: ?Sized>(
+    t: &mut T,
+    status: &TaskPresence,
+    options: &PrintOptions,
+) -> io::Result<()>
 where
-    T: Terminal,
+    T: Console,
 {
     t.attr(term::Attr::Bold)?;
+    write_wrapped(t, "", &status.title, options)?;
+    t.reset()?;
 
-    if boolean {
-        t.fg(term::color::GREEN)?;
-        write!(t, " ✔ ")?
-    } else {
+    write!(t, "Local:")?;
+    write_status(t, status.implemented_locally)?;
+
+    write!(t, "Remote:")?;
+    write_status(t, status.implemented_remotely)?;
+
+    writeln!(t, "")
+}
+
+/// Prints the crates that are used by the local implementation of a task but aren't declared in
+/// its manifest.
+fn print_undeclared_dependencies<T: ?Sized>(t: &mut T, task: &Task) -> io::Result<()>
+where
+    T: Console,
+{
+    let undeclared = task.undeclared_dependencies();
+
+    if !undeclared.is_empty() {
         t.fg(term::color::RED)?;
-        write!(t, " ✘ ")?;
+        write!(t, "Missing dependencies:")?;
+        t.reset()?;
+
+        writeln!(t, " {}", undeclared.join(", "))?;
+
+        if let Some(path) = task.local_path() {
+            writeln!(t, "Declare them in {}", path.join("Cargo.toml").display())?;
+        }
     }
 
-    t.reset()?;
     Ok(())
 }
 
-fn main() {
-    let matches = App::new("coverage")
-        .about(ABOUT)
-        .version(crate_version!())
-        .max_term_width(100)
-        .arg(
-            Arg::with_name("task")
-                .help("The name of a task on the wiki, such as 'K-d tree'")
-                .multiple(true),
-        )
-        .arg(
-            Arg::with_name("diff")
-                .help("Print diffs of tasks between the local and remote version")
-                .long("diff"),
-        )
-        .arg(
-            Arg::with_name("filter")
-                .help("Filter tasks printed by the program.")
-                .possible_values(&["all", "local", "remote", "unimplemented"])
-                .long("filter")
-                .takes_value(true),
-        )
-        .arg(
-            Arg::with_name("json-file")
-                .help("Dump json to the provided filename")
-                .long("json")
-                .takes_value(true),
-        )
-        .get_matches();
+/// Prints a warning if the local implementation of a task is a program without a `main` function.
+fn print_missing_main<T: ?Sized>(t: &mut T, task: &Task) -> io::Result<()>
+where
+    T: Console,
+{
+    if task.has_main() == Some(false) {
+        t.fg(term::color::RED)?;
+        write!(t, "Missing main:")?;
+        t.reset()?;
 
-    let mut t = term::stdout().unwrap();
+        match task.local_path() {
+            Some(path) => writeln!(t, " no `fn main` found in {}", path.display())?,
+            None => writeln!(t, " no `fn main` found")?,
+        }
+    }
 
-    let filter = value_t!(matches.value_of("filter"), Filter)
-        .ok()
-        .unwrap_or_default();
+    Ok(())
+}
 
-    let task_index = TaskIndex::create(env!("CARGO_MANIFEST_DIR")).unwrap();
+/// The fields that can be selected for the JSON output with `--json-fields`.
+const JSON_FIELDS: &[&str] = &[
+    "title",
+    "url",
+    "local",
+    "remote",
+    "paths",
+    "diff",
+    "sizes",
+    "checksums",
+    "description",
+];
 
-    let tasks = if let Some(tasks) = matches.values_of("task") {
-        let task_names = tasks.map(String::from).collect::<Vec<_>>();
-        task_index.fetch_tasks(&task_names)
-    } else {
-        task_index.fetch_all_tasks()
-    };
+/// The field of `JSON_FIELDS` that is only included by default with `--include-description`.
+const DESCRIPTION_FIELD: &str = "description";
 
-    let tasks = tasks
-        .flat_map(|task| {
-            let task = task.unwrap();
+/// The number of characters of a description that are printed to the terminal.
+const MAX_DESCRIPTION_CHARS: usize = 200;
 
-            match filter {
-                Filter::LocalOnly if !task.is_local_only() => return None,
-                Filter::RemoteOnly if !task.is_remote_only() => return None,
-                Filter::Unimplemented if !task.is_unimplemented() => return None,
-                Filter::All | _ => {}
-            }
+/// The columns that `--columns` can select.
+const COLUMNS: &[&str] = &[
+    "title", "local", "remote", "lines", "diff", "category", "modified",
+];
+
+/// The width that each column is padded to.
+fn column_width(column: &str) -> usize {
+    match column {
+        "title" => 40,
+        "category" => 30,
+        "modified" => 12,
+        _ => 7,
+    }
+}
 
-            print_task(&mut *t, &task, matches.is_present("diff")).unwrap();
+/// Formats how long ago a file was modified, in days.
+fn format_age(time: SystemTime) -> String {
+    let days = SystemTime::now()
+        .duration_since(time)
+        .map(|age| age.as_secs() / (60 * 60 * 24))
+        .unwrap_or(0);
 
-            if matches.is_present("json-file") {
-                let json = json!({
-                    "title": task.title(),
-                    "url": task.url().to_string(),
-                    "local_code": task.local_code(),
-                    "remote_code": task.remote_code(),
-                    "path": task.local_path(),
-                });
-
-                Some(json)
-            } else {
-                None
-            }
-        })
+    match days {
+        0 => String::from("today"),
+        1 => String::from("1 day ago"),
+        days => format!("{} days ago", days),
+    }
+}
+
+/// Returns the value of a column for a task. `categories` maps the title of each task to its
+/// categories.
+fn column_value(task: &Task, column: &str, categories: &BTreeMap<String, Vec<String>>) -> String {
+    let mark = |present: bool| String::from(if present { "✓" } else { "✗" });
+
+    match column {
+        "title" => task.title(),
+        "local" => mark(task.local_code().is_some()),
+        "remote" => mark(task.remote_code().is_some()),
+        "lines" => task
+            .local_line_count()
+            .map_or_else(|| String::from("-"), |lines| lines.to_string()),
+        "diff" => match (task.remote_code(), task.local_code()) {
+            (Some(remote), Some(local)) => diff::changed_lines(&remote, &local).to_string(),
+            _ => String::from("-"),
+        },
+        "category" => categories
+            .get(&task.title())
+            .map_or_else(|| String::from("-"), |categories| categories.join(", ")),
+        "modified" => task
+            .local_modified()
+            .map_or_else(|| String::from("-"), format_age),
+        _ => unreachable!("unknown column '{}'", column),
+    }
+}
+
+/// Prints a row of the table selected by `--columns`, padding each cell to its column's width.
+fn print_row<T: ?Sized>(t: &mut T, cells: &[(&str, String)]) -> io::Result<()>
+where
+    T: Console,
+{
+    let row = cells
+        .iter()
+        .map(|&(column, ref cell)| format!("{:1$}", cell, column_width(column)))
         .collect::<Vec<_>>();
+    writeln!(t, "{}", row.join(" ").trim_right())
+}
 
-    if let Some(filename) = matches.value_of("json-file") {
-        let mut file = File::create(filename).unwrap();
-        file.write_all(serde_json::to_string_pretty(&tasks).unwrap().as_bytes())
-            .unwrap();
+/// Returns the directory of a task's crate. If `root` is given, the path is relative to it, so
+/// reports don't depend on where the repository was checked out. Otherwise, it is the path
+/// returned by `Task::local_path`.
+fn task_dir(task: &Task, root: Option<&Path>) -> Option<PathBuf> {
+    match root {
+        Some(root) => task
+            .manifest_dir()?
+            .strip_prefix(root)
+            .ok()
+            .map(Path::to_path_buf),
+        None => task.local_path(),
+    }
+}
+
+/// Builds the JSON representation of a task, only including the given fields. Paths are relative
+/// to `paths_root` if it is given. See `task_dir`.
+fn task_json(
+    task: &Task,
+    fields: &[&str],
+    url_style: UrlStyle,
+    paths_root: Option<&Path>,
+) -> serde_json::Value {
+    let mut json = serde_json::Map::new();
+
+    for field in fields {
+        match *field {
+            "title" => {
+                json.insert("title".into(), json!(task.title()));
+            }
+            "url" => {
+                json.insert("url".into(), json!(task.url_with_style(url_style)));
+            }
+            "local" => {
+                json.insert("local_code".into(), json!(task.local_code()));
+            }
+            "remote" => {
+                json.insert("remote_code".into(), json!(task.remote_code()));
+            }
+            "paths" => {
+                json.insert("path".into(), json!(task_dir(task, paths_root)));
+            }
+            "diff" => {
+                json.insert("diff_kind".into(), json!(task.diff_kind()));
+                json.insert("diff_ratio".into(), json!(task.diff_ratio()));
+            }
+            "sizes" => {
+                json.insert("local_size_bytes".into(), json!(task.local_size_bytes()));
+                json.insert("remote_size_bytes".into(), json!(task.remote_size_bytes()));
+                json.insert(
+                    "remote_page_size_bytes".into(),
+                    json!(task.remote_page_size_bytes()),
+                );
+            }
+            "checksums" => {
+                json.insert("local_checksum".into(), json!(task.local_checksum()));
+                json.insert("remote_checksum".into(), json!(task.remote_checksum()));
+            }
+            "description" => {
+                json.insert("description".into(), json!(task.description()));
+            }
+            _ => unreachable!("unknown JSON field '{}'", field),
+        }
+    }
+
+    serde_json::Value::Object(json)
+}
+
+/// Parses a similarity ratio between 0 and 1.
+fn parse_ratio(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(ratio) if ratio >= 0.0 && ratio <= 1.0 => Ok(ratio),
+        _ => Err(String::from("expected a number from 0 to 1")),
+    }
+}
+
+/// Reads task names, one per line. Blank lines and comments starting with `#` are skipped.
+fn read_titles<R: BufRead>(reader: R) -> io::Result<Vec<String>> {
+    let mut titles = vec![];
+
+    for line in reader.lines() {
+        let line = line?;
+        let title = line.trim();
+
+        if !title.is_empty() && !title.starts_with('#') {
+            titles.push(title.to_owned());
+        }
+    }
+
+    Ok(titles)
+}
+
+/// Builds the JSON object that is printed to stderr when tasks could not be fetched and the output
+/// is JSON.
+///
+/// The object has a single `errors` array, with an entry for each task that could not be fetched.
+/// Each entry has the `title` of the task, the `kind` of failure (`network`, `parse` or `other`),
+/// and a human-readable `message`. The title is `null` if the error isn't about specific tasks.
+fn errors_json(errors: &[Error]) -> serde_json::Value {
+    let mut entries = vec![];
+
+    for err in errors {
+        match *err {
+            Error::Fetch(ref err) => entries.extend(err.titles.iter().map(|title| {
+                json!({
+                    "title": title,
+                    "kind": err.kind,
+                    "message": err.message,
+                })
+            })),
+            _ => entries.push(json!({
+                "title": null,
+                "kind": FetchErrorKind::Other,
+                "message": err.to_string(),
+            })),
+        }
+    }
+
+    json!({ "errors": entries })
+}
+
+/// Reads a map of task titles to revision IDs, as written by `--save-revisions`.
+fn read_revisions(path: &str) -> Result<BTreeMap<String, u64>, String> {
+    let json = report::read_report(path).map_err(|err| err.to_string())?;
+    serde_json::from_str(&json).map_err(|err| err.to_string())
+}
+
+/// True if the tasks of a report, written by `--json-file`, include their remote code. The
+/// parsed report can't tell, because a missing field reads the same as a task without code.
+fn has_remote_code(json: &str) -> bool {
+    let report = match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(report) => report,
+        Err(_) => return false,
+    };
+    let tasks = report.get("tasks").unwrap_or(&report);
+
+    tasks.as_array().map_or(false, |tasks| {
+        tasks.iter().any(|task| task.get("remote_code").is_some())
+    })
+}
+
+/// The code on the wiki that local tasks are compared against, as selected by `--diff-against`.
+enum RemoteSource {
+    /// The latest revision of each task.
+    Latest,
+
+    /// The remote code of each task in a report written by `--json-file`.
+    Snapshot(BTreeMap<String, Option<String>>),
+
+    /// The code of each task at the revision stored by `--save-revisions`.
+    Revision(BTreeMap<String, Option<String>>),
+}
+
+impl RemoteSource {
+    /// Resolves the selected source, reading the snapshot or requesting the stored revisions.
+    fn from_matches(matches: &clap::ArgMatches, task_index: &TaskIndex) -> Result<Self, String> {
+        match matches.value_of("diff-against") {
+            Some("snapshot") => {
+                let path = matches.value_of("snapshot").unwrap();
+                let json = report::read_report(path)
+                    .map_err(|err| format!("Could not read the report {}: {}", path, err))?;
+                let report = CoverageReport::from_json(&json)
+                    .map_err(|err| format!("Could not read the report {}: {}", path, err))?;
+
+                // A report written without the `remote` field would make every task look
+                // unimplemented on the wiki.
+                if !report.tasks.is_empty() && !has_remote_code(&json) {
+                    return Err(format!(
+                        "The report {} has no remote code, write it with the `remote` field of \
+                         `--json-fields`",
+                        path
+                    ));
+                }
+
+                let code = report
+                    .tasks
+                    .into_iter()
+                    .filter_map(|task| Some((task.title?, task.remote_code)))
+                    .collect();
+                Ok(RemoteSource::Snapshot(code))
+            }
+            Some("revision") => {
+                let path = matches.value_of("revisions").unwrap();
+                let revisions = read_revisions(path)
+                    .map_err(|err| format!("Could not read the revisions in {}: {}", path, err))?;
+
+                let ids = revisions.values().cloned().collect::<Vec<_>>();
+                let code = task_index
+                    .revision_code(&ids)
+                    .map_err(|err| format!("Could not fetch the stored revisions: {}", err))?;
+                Ok(RemoteSource::Revision(code))
+            }
+            _ => Ok(RemoteSource::Latest),
+        }
+    }
+
+    /// Replaces the remote code of a task with the code from this source.
+    fn apply(&self, task: Task) -> Task {
+        match *self {
+            RemoteSource::Latest => task,
+            RemoteSource::Snapshot(ref code) | RemoteSource::Revision(ref code) => {
+                let remote_code = code.get(&task.title()).cloned().unwrap_or(None);
+                task.with_remote_code(remote_code)
+            }
+        }
+    }
+}
+
+/// Parses a duration such as `90`, `90s`, `5m` or `2h`. Numbers without a unit are seconds.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (number, multiplier) = match s.chars().last() {
+        Some('s') => (&s[..s.len() - 1], 1),
+        Some('m') => (&s[..s.len() - 1], 60),
+        Some('h') => (&s[..s.len() - 1], 60 * 60),
+        _ => (s, 1),
+    };
+
+    number
+        .parse::<u64>()
+        .map(|n| Duration::from_secs(n * multiplier))
+        .map_err(|_| format!("invalid duration '{}', expected e.g. '5m'", s))
+}
+
+/// Writes a unified diff from the wiki to the local implementation of a task into `dir`.
+///
+/// Returns the path of the patch, or `None` if there is nothing to compare or the lines of the
+/// code are the same.
+fn write_patch(dir: &Path, task: &Task) -> io::Result<Option<PathBuf>> {
+    let (local_code, remote_code) = match (task.local_code(), task.remote_code()) {
+        (Some(local_code), Some(remote_code)) => (local_code, remote_code),
+        _ => return Ok(None),
+    };
+
+    let local_label = match task.local_path() {
+        Some(path) => path.display().to_string(),
+        None => format!("{} (local)", task.title()),
+    };
+
+    let patch = diff::unified_diff(
+        &remote_code,
+        &local_code,
+        &format!("{} (wiki)", task.title()),
+        &local_label,
+        3,
+    );
+
+    if patch.is_empty() {
+        return Ok(None);
+    }
+
+    let path = dir.join(format!("{}.patch", slugify(&task.title())));
+    File::create(&path)?.write_all(patch.as_bytes())?;
+
+    Ok(Some(path))
+}
+
+/// Writes the code of a task on the wiki into `dir`, in a file named after the slug of its title.
+/// Existing files are only replaced if `overwrite` is true.
+///
+/// `written` maps the files written earlier in the same run to the titles of their tasks. Different
+/// titles can have the same slug, such as "A+B" and "A plus B", so writing a file that another task
+/// wrote is an error, even with `overwrite`.
+///
+/// Returns the path of the file, or `None` if the task has no code on the wiki.
+fn write_remote_code(
+    dir: &Path,
+    task: &Task,
+    overwrite: bool,
+    written: &mut BTreeMap<PathBuf, String>,
+) -> io::Result<Option<PathBuf>> {
+    let code = match task.remote_code() {
+        Some(code) => code,
+        None => return Ok(None),
+    };
+
+    let path = dir.join(format!("{}.rs", slugify(&task.title())));
+    if let Some(other) = written.get(&path) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} was already written for '{}'", path.display(), other),
+        ));
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .create_new(!overwrite)
+        .open(&path)
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::AlreadyExists => io::Error::new(
+                err.kind(),
+                format!(
+                    "{} already exists, use --overwrite to replace it",
+                    path.display()
+                ),
+            ),
+            _ => err,
+        })?;
+    file.write_all(code.as_bytes())?;
+    written.insert(path.clone(), task.title());
+
+    Ok(Some(path))
+}
+
+/// Counts of implemented and total tasks, grouped by the first letter of their titles.
+#[derive(Debug, Default)]
+struct Heatmap {
+    buckets: BTreeMap<char, (usize, usize)>,
+}
+
+impl Heatmap {
+    /// Adds a task to the bucket of its first letter. Titles that don't start with a letter are
+    /// grouped together under `#`.
+    fn add(&mut self, title: &str, implemented: bool) {
+        let letter = match title.chars().next() {
+            Some(c) if c.is_alphabetic() => c.to_uppercase().next().unwrap(),
+            _ => '#',
+        };
+
+        let bucket = self.buckets.entry(letter).or_insert((0, 0));
+        if implemented {
+            bucket.0 += 1;
+        }
+        bucket.1 += 1;
+    }
+}
+
+/// Returns the color of a heatmap cell, from red for low coverage to bright green for high
+/// coverage.
+fn heatmap_color(implemented: usize, total: usize) -> term::color::Color {
+    let ratio = implemented as f64 / total as f64;
+
+    if ratio < 0.2 {
+        term::color::RED
+    } else if ratio < 0.4 {
+        term::color::YELLOW
+    } else if ratio < 0.6 {
+        term::color::BRIGHT_YELLOW
+    } else if ratio < 0.8 {
+        term::color::GREEN
+    } else {
+        term::color::BRIGHT_GREEN
+    }
+}
+
+/// Prints a grid with the number of implemented and total tasks for each letter.
+fn print_heatmap<T: ?Sized>(t: &mut T, heatmap: &Heatmap) -> io::Result<()>
+where
+    T: Console,
+{
+    const COLUMNS: usize = 6;
+
+    for (i, (letter, &(implemented, total))) in heatmap.buckets.iter().enumerate() {
+        if i > 0 {
+            write!(t, "{}", if i % COLUMNS == 0 { "\n" } else { "  " })?;
+        }
+
+        t.attr(term::Attr::Bold)?;
+        write!(t, "{}", letter)?;
+        t.reset()?;
+
+        t.fg(heatmap_color(implemented, total))?;
+        write!(t, " {:>3}/{:<3}", implemented, total)?;
+        t.reset()?;
+    }
+
+    writeln!(t)?;
+    t.flush()
+}
+
+/// The exclusive upper bounds of the buckets of the histograms printed by `stats`. The last bucket
+/// has no upper bound.
+const HISTOGRAM_BOUNDS: &[usize] = &[10, 25, 50, 100, 200, 500, 1000];
+
+/// Counts of values, grouped into the ranges given by `HISTOGRAM_BOUNDS`.
+#[derive(Debug)]
+struct Histogram {
+    counts: Vec<usize>,
+}
+
+impl Histogram {
+    fn new() -> Histogram {
+        Histogram {
+            counts: vec![0; HISTOGRAM_BOUNDS.len() + 1],
+        }
+    }
+
+    fn add(&mut self, value: usize) {
+        let bucket = HISTOGRAM_BOUNDS
+            .iter()
+            .position(|&bound| value < bound)
+            .unwrap_or(HISTOGRAM_BOUNDS.len());
+        self.counts[bucket] += 1;
+    }
+
+    /// Returns the inclusive range of each bucket. The last range has no maximum.
+    fn ranges() -> Vec<(usize, Option<usize>)> {
+        let mut min = 0;
+        let mut ranges = vec![];
+
+        for &bound in HISTOGRAM_BOUNDS {
+            ranges.push((min, Some(bound - 1)));
+            min = bound;
+        }
+        ranges.push((min, None));
+
+        ranges
+    }
+
+    /// Returns a row of a bar chart for each bucket.
+    fn rows(&self) -> Vec<(String, usize)> {
+        Histogram::ranges()
+            .into_iter()
+            .zip(&self.counts)
+            .map(|((min, max), &count)| match max {
+                Some(max) => (format!("{}-{}", min, max), count),
+                None => (format!("{}+", min), count),
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let buckets = Histogram::ranges()
+            .into_iter()
+            .zip(&self.counts)
+            .map(|((min, max), count)| json!({ "min": min, "max": max, "count": count }))
+            .collect::<Vec<_>>();
+
+        json!(buckets)
+    }
+}
+
+/// Prints a horizontal bar chart with a row for each label. The bars are scaled to fit into
+/// `width` columns.
+fn print_bar_chart<T: ?Sized>(
+    t: &mut T,
+    title: &str,
+    rows: &[(String, usize)],
+    width: usize,
+) -> io::Result<()>
+where
+    T: Console,
+{
+    t.attr(term::Attr::Bold)?;
+    writeln!(t, "{}", title)?;
+    t.reset()?;
+
+    let label_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0);
+    let max = rows.iter().map(|row| row.1).max().unwrap_or(0);
+    let count_width = max.to_string().len();
+
+    // Leave room for the label, the count, and the spaces between them.
+    let bar_width = width.saturating_sub(label_width + count_width + 3).max(1);
+
+    for &(ref label, count) in rows {
+        let bar = if max == 0 {
+            0
+        } else {
+            (count * bar_width + max - 1) / max
+        };
+
+        write!(t, "{:>width$} ", label, width = label_width)?;
+        t.fg(term::color::GREEN)?;
+        write!(t, "{}", "#".repeat(bar))?;
+        t.reset()?;
+        writeln!(t, " {}", count)?;
+    }
+
+    Ok(())
+}
+
+/// Prints statistics over the tasks: histograms of the number of lines of local code and of the
+/// size of the diffs, and the number of locally implemented tasks in each category.
+///
+/// Every task is fetched from the wiki. With `json`, the raw buckets are printed as JSON instead.
+fn print_stats<T: ?Sized>(t: &mut T, task_index: &TaskIndex, json: bool) -> io::Result<()>
+where
+    T: Console,
+{
+    let mut line_counts = Histogram::new();
+    let mut diff_sizes = Histogram::new();
+
+    for task in task_index.fetch_all_tasks() {
+        let task = match task {
+            Ok(task) => task,
+            Err(err) => {
+                eprintln!("{}", err);
+                continue;
+            }
+        };
+
+        if let Some(local_code) = task.local_code() {
+            line_counts.add(local_code.lines().count());
+
+            if let Some(remote_code) = task.remote_code() {
+                diff_sizes.add(diff::changed_lines(&remote_code, &local_code));
+            }
+        }
+    }
+
+    let categories = match task_index.categories() {
+        Ok(categories) => categories,
+        Err(err) => {
+            eprintln!("Could not fetch the categories of the tasks: {}", err);
+            BTreeMap::new()
+        }
+    };
+
+    // Categories with the most implemented tasks come first.
+    let mut category_counts = categories
+        .iter()
+        .map(|(category, titles)| {
+            let implemented = titles
+                .iter()
+                .filter(|title| task_index.is_implemented_locally(title))
+                .count();
+            (category.clone(), implemented, titles.len())
+        })
+        .collect::<Vec<_>>();
+    category_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    if json {
+        let categories = category_counts
+            .iter()
+            .map(|&(ref category, implemented, total)| {
+                json!({ "category": category, "implemented": implemented, "total": total })
+            })
+            .collect::<Vec<_>>();
+
+        let stats = json!({
+            "local_lines": line_counts.to_json(),
+            "diff_lines": diff_sizes.to_json(),
+            "categories": categories,
+        });
+        return writeln!(t, "{}", serde_json::to_string_pretty(&stats).unwrap());
+    }
+
+    let width = term_size::dimensions().map_or(80, |(width, _)| width);
+
+    print_bar_chart(t, "Lines of local code", &line_counts.rows(), width)?;
+    writeln!(t)?;
+    print_bar_chart(t, "Lines changed from the wiki", &diff_sizes.rows(), width)?;
+    writeln!(t)?;
+
+    let category_rows = category_counts
+        .into_iter()
+        .map(|(category, implemented, _)| (category, implemented))
+        .collect::<Vec<_>>();
+    print_bar_chart(t, "Implemented tasks per category", &category_rows, width)
+}
+
+/// The sections of `--group-by status`, in the order that they are printed.
+const STATUS_GROUPS: &[&str] = &["Both", "Local only", "Remote only", "Unimplemented"];
+
+/// Returns the index of the section in `STATUS_GROUPS` that a task belongs to.
+fn status_group(task: &Task) -> usize {
+    if task.is_local_only() {
+        1
+    } else if task.is_remote_only() {
+        2
+    } else if task.is_unimplemented() {
+        3
+    } else {
+        0
+    }
+}
+
+/// Prints the titles of tasks under the header of their section, given the titles in each
+/// section of `STATUS_GROUPS`. Empty sections are skipped, so a filter hides the sections that it
+/// excludes.
+fn print_status_groups<T: ?Sized>(t: &mut T, groups: &[Vec<String>]) -> io::Result<()>
+where
+    T: Console,
+{
+    let sections = STATUS_GROUPS
+        .iter()
+        .zip(groups)
+        .filter(|&(_, titles)| !titles.is_empty());
+
+    for (i, (name, titles)) in sections.enumerate() {
+        if i > 0 {
+            writeln!(t)?;
+        }
+
+        t.attr(term::Attr::Bold)?;
+        writeln!(t, "{} ({})", name, titles.len())?;
+        t.reset()?;
+
+        for title in titles {
+            writeln!(t, "  {}", title)?;
+        }
+    }
+
+    t.flush()
+}
+
+/// Prints a task whose directory doesn't match its title, and the directory to rename it to.
+fn print_naming_mismatch<T: ?Sized>(t: &mut T, mismatch: &NamingMismatch) -> io::Result<()>
+where
+    T: Console,
+{
+    t.attr(term::Attr::Bold)?;
+    write!(t, "{}", mismatch.title)?;
+    t.reset()?;
+
+    if let Some(title) = local::dir_title(&mismatch.dir) {
+        write!(t, " (directory reads as '{}')", title)?;
+    }
+    writeln!(t)?;
+
+    writeln!(
+        t,
+        "  rename {} to {}",
+        mismatch.dir.display(),
+        mismatch.suggested_dir.display()
+    )
+}
+
+/// Prints the markers of incomplete work in the code of a task, one per line. Paths are relative
+/// to `root` if they are inside it.
+fn print_todos<T: ?Sized>(
+    t: &mut T,
+    title: &str,
+    markers: &[TodoMarker],
+    root: &Path,
+) -> io::Result<()>
+where
+    T: Console,
+{
+    t.attr(term::Attr::Bold)?;
+    writeln!(t, "{}", title)?;
+    t.reset()?;
+
+    for marker in markers {
+        let path = marker.path.strip_prefix(root).unwrap_or(&marker.path);
+        writeln!(t, "  {}:{}: {}", path.display(), marker.line, marker.text)?;
+    }
+
+    Ok(())
+}
+
+/// The headings of the sections of an audit, and the tasks listed in each.
+fn audit_sections(audit: &Audit) -> [(&'static str, &[String]); 2] {
+    [
+        (
+            "Tasks on the wiki that are not implemented",
+            &audit.unimplemented[..],
+        ),
+        (
+            "Local tasks that are not on the wiki",
+            &audit.missing_on_wiki[..],
+        ),
+    ]
+}
+
+/// Prints the tasks of an audit that are missing on either side, under a heading per section.
+fn print_audit<T: ?Sized>(t: &mut T, audit: &Audit) -> io::Result<()>
+where
+    T: Console,
+{
+    for &(heading, titles) in &audit_sections(audit) {
+        t.attr(term::Attr::Bold)?;
+        writeln!(t, "{} ({})", heading, titles.len())?;
+        t.reset()?;
+
+        for title in titles {
+            writeln!(t, "  {}", title)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The outcome of one of the checks of the `check` subcommand.
+struct CheckResult {
+    /// The name of the check, one of `CHECKS`.
+    name: &'static str,
+
+    /// The problems that the check found. The check passed if there are none.
+    problems: Vec<String>,
+}
+
+impl CheckResult {
+    /// If the check could not be run, the error is its only problem.
+    fn new(name: &'static str, problems: Result<Vec<String>, Error>) -> CheckResult {
+        let problems =
+            problems.unwrap_or_else(|err| vec![format!("could not run the check: {}", err)]);
+        CheckResult { name, problems }
+    }
+}
+
+/// Validates a comma-separated list of checks.
+fn validate_checks(checks: String) -> Result<(), String> {
+    match checks.split(',').find(|check| !CHECKS.contains(check)) {
+        Some(check) => Err(format!(
+            "unknown check '{}', expected some of {}",
+            check,
+            CHECKS.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Runs cargo in the workspace. Its output is printed as it runs. If it fails, the only problem is
+/// the failed command.
+fn run_cargo(manifest_dir: &str, args: &[&str]) -> Result<Vec<String>, Error> {
+    let status = process::Command::new("cargo")
+        .args(args)
+        .current_dir(manifest_dir)
+        .status()?;
+
+    if status.success() {
+        Ok(vec![])
+    } else {
+        let command = format!("cargo {}", args.join(" "));
+        Ok(vec![format!("`{}` failed: {}", command, status)])
+    }
+}
+
+/// Reads the titles of the local tasks and of the omitted tasks of the workspace.
+fn read_local_titles(
+    manifest_dir: &str,
+    cache_path: &Path,
+) -> Result<(BTreeSet<String>, BTreeSet<String>), Error> {
+    let manifest_path = Path::new(manifest_dir).join("Cargo.toml");
+    let options = local::ScanOptions::for_workspace(Path::new(manifest_dir))?;
+
+    let local_titles = local::parse_tasks_cached(&manifest_path, cache_path, &options)?
+        .into_iter()
+        .map(|task| task.title)
+        .collect();
+    let omitted_titles = local::parse_omitted_titles(&manifest_path)?;
+
+    Ok((local_titles, omitted_titles))
+}
+
+/// Runs the enabled checks of the `check` subcommand, in the order of `CHECKS`.
+fn run_checks(
+    enabled: &[&str],
+    manifest_dir: &str,
+    cache_path: &Path,
+    client: Box<WikiClient>,
+) -> Vec<CheckResult> {
+    let is_enabled = |check| enabled.contains(&check);
+    let mut results = vec![];
+
+    if is_enabled("build") {
+        let problems = run_cargo(manifest_dir, &["check", "--all"]);
+        results.push(CheckResult::new("build", problems));
+    }
+
+    if is_enabled("fmt") {
+        let problems = run_cargo(manifest_dir, &["fmt", "--all", "--", "--write-mode=diff"]);
+        results.push(CheckResult::new("fmt", problems));
+    }
+
+    if is_enabled("links") {
+        let problems = read_local_titles(manifest_dir, cache_path)
+            .and_then(|(local_titles, omitted_titles)| {
+                audit::audit(&*client, &local_titles, &omitted_titles)
+            })
+            .map(|audit| {
+                audit
+                    .missing_on_wiki
+                    .iter()
+                    .map(|title| format!("'{}' is not listed on the wiki", title))
+                    .collect()
+            });
+        results.push(CheckResult::new("links", problems));
+    }
+
+    if is_enabled("orphans") {
+        let manifest_path = Path::new(manifest_dir).join("Cargo.toml");
+        let problems = local::IgnorePatterns::read(Path::new(manifest_dir))
+            .and_then(|ignore| local::orphaned_crates(&manifest_path, &ignore))
+            .map(|dirs| {
+                dirs.iter()
+                    .map(|dir| format!("{} is not a member of the workspace", dir.display()))
+                    .collect()
+            });
+        results.push(CheckResult::new("orphans", problems));
+    }
+
+    let index_checks = ["naming", "parse-errors"];
+    if !index_checks.iter().any(|&check| is_enabled(check)) {
+        return results;
+    }
+
+    // Both checks need the index, which can only be created if the links check passes.
+    let task_index = match TaskIndex::builder(manifest_dir)
+        .index_cache(cache_path)
+        .client(client)
+        .build()
+    {
+        Ok(task_index) => task_index,
+        Err(err) => {
+            for &check in index_checks.iter().filter(|&&check| is_enabled(check)) {
+                let problem = format!("could not create the task index: {}", err);
+                results.push(CheckResult::new(check, Ok(vec![problem])));
+            }
+            return results;
+        }
+    };
+
+    if is_enabled("naming") {
+        let problems = task_index
+            .naming_mismatches()
+            .iter()
+            .map(|mismatch| {
+                format!(
+                    "'{}' is in {}, but should be in {}",
+                    mismatch.title,
+                    mismatch.dir.display(),
+                    mismatch.suggested_dir.display()
+                )
+            })
+            .collect();
+        results.push(CheckResult::new("naming", Ok(problems)));
+    }
+
+    if is_enabled("parse-errors") {
+        let titles = task_index
+            .all_titles()
+            .filter(|title| task_index.is_implemented_locally(title))
+            .filter(|title| !task_index.is_omitted(title))
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        // An empty list would fetch every task.
+        let mut problems = vec![];
+        if !titles.is_empty() {
+            for task in task_index.fetch_tasks(&titles) {
+                match task {
+                    Ok(ref task) if task.is_unparseable() => problems.push(format!(
+                        "could not parse the Rust code of '{}' on the wiki",
+                        task.title()
+                    )),
+                    Ok(_) => {}
+                    Err(err) => problems.push(err.to_string()),
+                }
+            }
+        }
+        results.push(CheckResult::new("parse-errors", Ok(problems)));
+    }
+
+    results
+}
+
+/// Prints whether each check passed or failed, followed by the problems of the failed checks.
+/// Checks that were not run are listed as skipped.
+fn print_check_results<T: ?Sized>(t: &mut T, results: &[CheckResult]) -> io::Result<()>
+where
+    T: Console,
+{
+    t.attr(term::Attr::Bold)?;
+    writeln!(t, "Checks")?;
+    t.reset()?;
+
+    for &name in CHECKS {
+        write!(t, "  {:<14}", name)?;
+
+        let result = match results.iter().find(|result| result.name == name) {
+            Some(result) => result,
+            None => {
+                t.fg(term::color::YELLOW)?;
+                write!(t, "skipped")?;
+                t.reset()?;
+                writeln!(t)?;
+                continue;
+            }
+        };
+
+        if result.problems.is_empty() {
+            t.fg(term::color::GREEN)?;
+            write!(t, "passed")?;
+            t.reset()?;
+            writeln!(t)?;
+        } else {
+            t.fg(term::color::RED)?;
+            write!(t, "failed")?;
+            t.reset()?;
+            writeln!(
+                t,
+                " ({} {})",
+                result.problems.len(),
+                if result.problems.len() == 1 {
+                    "problem"
+                } else {
+                    "problems"
+                }
+            )?;
+
+            for problem in &result.problems {
+                writeln!(t, "    {}", problem)?;
+            }
+        }
+    }
+
+    let failed = results
+        .iter()
+        .filter(|result| !result.problems.is_empty())
+        .count();
+    writeln!(
+        t,
+        "\n{} of {} checks passed",
+        results.len() - failed,
+        results.len()
+    )
+}
+
+/// Quotes a path for a POSIX shell, unless it only has characters that the shell doesn't treat
+/// specially.
+fn shell_quote(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let is_plain = |c: char| c.is_ascii_alphanumeric() || "-_./".contains(c);
+
+    if !path.is_empty() && path.chars().all(is_plain) {
+        path.into_owned()
+    } else {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    }
+}
+
+/// Formats the shell commands that move the crates of renamed tasks to the directories of their
+/// new titles, to be run from `root`. The parent directory of a subpage is created first if it
+/// doesn't exist yet.
+fn rename_commands(renames: &[Rename], root: &Path) -> String {
+    let mut commands = String::new();
+    let mut created = BTreeSet::new();
+
+    for rename in renames {
+        commands.push_str(&format!("# {} -> {}\n", rename.title, rename.new_title));
+
+        if rename.dir == rename.new_dir {
+            commands.push_str("# The directory already matches, only the URL needs updating.\n");
+            continue;
+        }
+
+        if let Some(parent) = rename.new_dir.parent() {
+            if !root.join(parent).is_dir() && created.insert(parent.to_owned()) {
+                commands.push_str(&format!("mkdir -p {}\n", shell_quote(parent)));
+            }
+        }
+
+        commands.push_str(&format!(
+            "git mv {} {}\n",
+            shell_quote(&rename.dir),
+            shell_quote(&rename.new_dir)
+        ));
+    }
+
+    commands
+}
+
+/// Formats an audit as Markdown, with a list of tasks under each heading.
+fn audit_markdown(audit: &Audit) -> String {
+    let mut markdown = String::new();
+
+    for &(heading, titles) in &audit_sections(audit) {
+        if !markdown.is_empty() {
+            markdown.push('\n');
+        }
+        markdown.push_str(&format!("## {} ({})\n\n", heading, titles.len()));
+
+        for title in titles {
+            markdown.push_str(&format!("- {}\n", title));
+        }
+    }
+
+    markdown
+}
+
+/// Prints the tasks that are omitted from the listing, and the rules for omitting them.
+fn print_omitted<T: ?Sized>(t: &mut T, task_index: &TaskIndex) -> io::Result<()>
+where
+    T: Console,
+{
+    let omitted = task_index.omitted_titles().collect::<Vec<_>>();
+
+    t.attr(term::Attr::Bold)?;
+    writeln!(t, "Omitted tasks")?;
+    t.reset()?;
+
+    if omitted.is_empty() {
+        writeln!(t, "No tasks are omitted.")?;
+    } else {
+        for title in omitted {
+            writeln!(t, "  {}", title)?;
+        }
+    }
+
+    writeln!(
+        t,
+        "Tasks listed in `package.metadata.rosettacode.omit` in Cargo.toml are left out of the \
+         listing and the summary, unless they are requested by name."
+    )?;
+
+    Ok(())
+}
+
+/// Prints the aliases that were resolved to the titles of the requested tasks.
+fn print_aliases<T: ?Sized>(t: &mut T, applied_aliases: &[(String, String)]) -> io::Result<()>
+where
+    T: Console,
+{
+    if applied_aliases.is_empty() {
+        return Ok(());
+    }
+
+    t.attr(term::Attr::Bold)?;
+    writeln!(t, "Aliases")?;
+    t.reset()?;
+
+    for &(ref alias, ref title) in applied_aliases {
+        writeln!(t, "  {} -> {}", alias, title)?;
+    }
+
+    writeln!(
+        t,
+        "Aliases are listed in `package.metadata.rosettacode.aliases` in Cargo.toml."
+    )?;
+
+    Ok(())
+}
+
+/// Prints the number of tasks in each implementation status.
+fn print_summary<T: ?Sized>(t: &mut T, summary: &Summary) -> io::Result<()>
+where
+    T: Console,
+{
+    t.attr(term::Attr::Bold)?;
+    writeln!(t, "Summary")?;
+    t.reset()?;
+
+    let (green, red, yellow) = (
+        Some(term::color::GREEN),
+        Some(term::color::RED),
+        Some(term::color::YELLOW),
+    );
+    let rows = [
+        ("Total", summary.total, None),
+        ("Implemented", summary.implemented, green),
+        ("Both", summary.both, green),
+        ("Local only", summary.local_only, None),
+        ("Remote only", summary.remote_only, None),
+        ("Unimplemented", summary.unimplemented, red),
+        ("Differing", summary.differing, yellow),
+    ];
+
+    // The label is padded before the color is set, so that the numbers stay aligned.
+    for &(label, count, color) in &rows {
+        write!(t, "{:<16}", format!("{}:", label))?;
+        if let Some(color) = color {
+            t.fg(color)?;
+        }
+        write!(t, "{:>5}", count)?;
+        t.reset()?;
+        writeln!(t)?;
+    }
+
+    writeln!(t, "Coverage:       {:>5.2}%", summary.coverage_percent())?;
+
+    Ok(())
+}
+
+/// Writes a boolean as a pretty, human-readable string.
+fn write_status<T: ?Sized>(t: &mut T, boolean: bool) -> io::Result<()>
+where
+    T: Console,
+{
+    t.attr(term::Attr::Bold)?;
+
+    if boolean {
+        t.fg(term::color::GREEN)?;
+        write!(t, " ✔ ")?
+    } else {
+        t.fg(term::color::RED)?;
+        write!(t, " ✘ ")?;
+    }
+
+    t.reset()?;
+    Ok(())
+}
+
+/// The environment variable that holds the password to log in to the wiki with.
+const WIKI_PASSWORD_VAR: &str = "ROSETTA_WIKI_PASSWORD";
+
+/// Returns the password to log in to the wiki with. It is read from the environment, or else
+/// from the first line of stdin, so that it never shows up in the arguments of the process.
+fn wiki_password() -> io::Result<String> {
+    if let Ok(password) = env::var(WIKI_PASSWORD_VAR) {
+        return Ok(password);
+    }
+
+    if atty::is(atty::Stream::Stdin) {
+        eprint!("Password: ");
+    }
+
+    let mut password = String::new();
+    if io::stdin().read_line(&mut password)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("stdin is empty, set {} instead", WIKI_PASSWORD_VAR),
+        ));
+    }
+    Ok(password
+        .trim_right_matches(|c| c == '\n' || c == '\r')
+        .to_owned())
+}
+
+/// Writes the size of a piece of code, if it exists.
+fn write_size<T: ?Sized>(t: &mut T, size: Option<usize>) -> io::Result<()>
+where
+    T: Console,
+{
+    match size {
+        Some(size) => write!(t, " {} bytes ", size),
+        None => write!(t, " - "),
+    }
+}
+
+/// Writes JSON to a file, or to stdout if the filename is `-`. The JSON is pretty-printed unless
+/// it should be `compact`.
+fn write_json(filename: &str, json: &serde_json::Value, compact: bool) -> io::Result<()> {
+    let json = if compact {
+        serde_json::to_string(json)?
+    } else {
+        serde_json::to_string_pretty(json)?
+    };
+
+    if filename == "-" {
+        println!("{}", json);
+    } else {
+        report::write_report(filename, &json)?;
+    }
+
+    Ok(())
+}
+
+/// How a task differs between two checkouts of the repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    fn name(&self) -> &'static str {
+        match *self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+        }
+    }
+}
+
+/// A task whose local code differs between two checkouts of the repository.
+#[derive(Debug)]
+struct LocalChange {
+    title: String,
+    kind: ChangeKind,
+
+    /// The directory of the task's crate, in this checkout unless the task was removed.
+    path: PathBuf,
+
+    /// The code of the task in the other checkout.
+    old_code: Option<String>,
+
+    /// The code of the task in this checkout.
+    new_code: Option<String>,
+}
+
+/// Compares the tasks of this checkout against the tasks of another checkout, and returns the
+/// tasks that were added, removed or changed, sorted by title.
+fn local_changes(ours: &[LocalTask], theirs: &[LocalTask]) -> Vec<LocalChange> {
+    let ours = ours
+        .iter()
+        .map(|task| (task.title.as_str(), task))
+        .collect::<BTreeMap<_, _>>();
+    let theirs = theirs
+        .iter()
+        .map(|task| (task.title.as_str(), task))
+        .collect::<BTreeMap<_, _>>();
+    let titles = ours
+        .keys()
+        .chain(theirs.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    let mut changes = vec![];
+
+    for title in titles {
+        let old_code = theirs.get(title).map(|task| task.code());
+        let new_code = ours.get(title).map(|task| task.code());
+
+        let kind = match (&old_code, &new_code) {
+            (&None, &Some(_)) => ChangeKind::Added,
+            (&Some(_), &None) => ChangeKind::Removed,
+            (&Some(ref old_code), &Some(ref new_code)) if old_code != new_code => {
+                ChangeKind::Changed
+            }
+            _ => continue,
+        };
+
+        let task = ours.get(title).or_else(|| theirs.get(title)).unwrap();
+
+        changes.push(LocalChange {
+            title: title.to_owned(),
+            kind,
+            path: task.manifest_path.parent().unwrap().to_owned(),
+            old_code,
+            new_code,
+        });
+    }
+
+    changes
+}
+
+/// Prints a task that differs between two checkouts of the repository.
+fn print_local_change<T: ?Sized>(
+    t: &mut T,
+    change: &LocalChange,
+    options: &PrintOptions,
+) -> io::Result<()>
+where
+    T: Console,
+{
+    t.attr(term::Attr::Bold)?;
+    write_wrapped(t, "", &change.title, options)?;
+    t.reset()?;
+
+    t.fg(match change.kind {
+        ChangeKind::Added => term::color::GREEN,
+        ChangeKind::Removed => term::color::RED,
+        ChangeKind::Changed => term::color::YELLOW,
+    })?;
+    write!(t, "{}", change.kind.name())?;
+    t.reset()?;
+    writeln!(t, " {}", change.path.display())?;
+
+    if options.diff {
+        if let (&Some(ref old_code), &Some(ref new_code)) = (&change.old_code, &change.new_code) {
+            print_diff(t, &diff::changeset(old_code, new_code), options)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the tasks again whenever their local code changes, until the program is interrupted.
+///
+/// The tasks are only fetched from the wiki once, but their local code is read again for every
+/// update. Saves that happen in quick succession are combined into a single update.
+fn watch<T: ?Sized>(t: &mut T, tasks: &[Task], options: &PrintOptions) -> notify::Result<()>
+where
+    T: Console,
+{
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::watcher(tx, Duration::from_millis(300))?;
+
+    for path in tasks.iter().filter_map(Task::local_path) {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(path);
+        watcher.watch(dir, RecursiveMode::Recursive)?;
+    }
+
+    loop {
+        for task in tasks {
+            print_task(t, task, options)?;
+        }
+
+        loop {
+            match rx.recv() {
+                Ok(DebouncedEvent::Create(path))
+                | Ok(DebouncedEvent::Write(path))
+                | Ok(DebouncedEvent::Remove(path))
+                | Ok(DebouncedEvent::Rename(_, path)) => {
+                    t.fg(term::color::CYAN)?;
+                    writeln!(t, "\n{} changed", path.display())?;
+                    t.reset()?;
+                    break;
+                }
+                Ok(DebouncedEvent::Error(err, _)) => return Err(err),
+                Ok(_) => continue,
+                Err(_) => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Adds a crate to the `workspace.members` of the manifest of the repository, keeping the members
+/// sorted, with the URL of its task in a comment above it like the other members.
+///
+/// Returns the new contents of the manifest, or `None` if it has no list of members.
+fn add_workspace_member(manifest: &str, member: &str, url: &Url) -> Option<String> {
+    let mut lines = manifest.lines().collect::<Vec<_>>();
+    let start = lines.iter().position(|line| line.trim() == "members = [")?;
+    let end = start + lines[start..].iter().position(|line| line.trim() == "]")?;
+
+    let entry = format!("    \"{}\",", member);
+    let comment = format!("    # {}", url);
+    let next = (start + 1..end).find(|&i| {
+        let line = lines[i].trim();
+        line.starts_with('"') && line.trim_matches(|c| c == '"' || c == ',') > member
+    });
+
+    match next {
+        Some(mut i) => {
+            // Keep the comment of the next member above it.
+            if lines[i - 1].trim_left().starts_with('#') {
+                i -= 1;
+            }
+            lines.splice(i..i, vec![comment.as_str(), entry.as_str(), ""]);
+        }
+        None => {
+            lines.splice(end..end, vec!["", comment.as_str(), entry.as_str()]);
+        }
+    }
+
+    let mut manifest = lines.join("\n");
+    manifest.push('\n');
+    Some(manifest)
+}
+
+/// Creates a new crate for an unimplemented task at the conventional location in the repository,
+/// and adds it to the members of the workspace.
+///
+/// The header of the new `main.rs` links to the task and includes its description. Returns the
+/// path to the new crate. Refuses to overwrite any existing files, and checks that the crate can be
+/// added to the workspace before anything is created.
+fn scaffold(task: &Task) -> io::Result<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let relative_dir = local::task_dir(&task.title());
+    let crate_dir = root.join(&relative_dir);
+    let package_name = relative_dir.file_name().unwrap().to_string_lossy();
+
+    if crate_dir.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", crate_dir.display()),
+        ));
+    }
+
+    let member = relative_dir
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let workspace_manifest_path = root.join("Cargo.toml");
+    let mut workspace_manifest = String::new();
+    File::open(&workspace_manifest_path)?.read_to_string(&mut workspace_manifest)?;
+    let workspace_manifest = add_workspace_member(&workspace_manifest, &member, &task.url())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} has no `workspace.members` to add the crate to",
+                    workspace_manifest_path.display()
+                ),
+            )
+        })?;
+
+    fs::create_dir_all(crate_dir.join("src"))?;
+
+    let mut manifest = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(crate_dir.join("Cargo.toml"))?;
+    write!(
+        manifest,
+        r#"[package]
+name = "{}"
+version = "0.1.0"
+
+[package.metadata.rosettacode]
+url = "{}"
+
+[dependencies]
+"#,
+        package_name,
+        task.url()
+    )?;
+
+    let mut header = format!("// {}\n// {}\n", task.title(), task.url());
+    if let Some(description) = task.description() {
+        header.push_str("//\n");
+        for line in description.lines() {
+            header.push_str(format!("// {}", line).trim_right());
+            header.push('\n');
+        }
+    }
+
+    let mut main = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(crate_dir.join("src/main.rs"))?;
+    write!(
+        main,
+        r#"{}
+fn main() {{
+    // TODO: Implement this task.
+}}
+"#,
+        header
+    )?;
+
+    File::create(&workspace_manifest_path)?.write_all(workspace_manifest.as_bytes())?;
+
+    Ok(relative_dir)
+}
+
+fn main() {
+    let matches = App::new("coverage")
+        .about(ABOUT)
+        .version(crate_version!())
+        .max_term_width(100)
+        .arg(
+            Arg::with_name("task")
+                .help(
+                    "The name of a task on the wiki, such as 'K-d tree'. If no tasks are given \
+                     and stdin is not a terminal, the names are read from stdin, one per line. \
+                     '-' reads them from stdin in addition to the other names.",
+                )
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("diff")
+                .help("Print diffs of tasks between the local and remote version")
+                .long("diff"),
+        )
+        .arg(
+            Arg::with_name("classify")
+                .help("Annotate each hunk of a diff with the kind of code that it changes")
+                .long("classify")
+                .requires("diff"),
+        )
+        .arg(
+            Arg::with_name("detect-moves")
+                .help("Show blocks of code that were moved within a diff in blue")
+                .long("detect-moves")
+                .requires("diff"),
+        )
+        .arg(
+            Arg::with_name("no-diff-context-for-identical")
+                .help(
+                    "Print a single line instead of a diff for tasks whose code is identical, \
+                     which is much faster when most tasks match",
+                )
+                .long("no-diff-context-for-identical")
+                .requires("diff"),
+        )
+        .arg(
+            Arg::with_name("streaming-diff")
+                .help(
+                    "Always use the streaming diff, which needs little memory but may show larger \
+                     diffs than necessary. Applies to every comparison, including diff ratios, \
+                     line counts and patches",
+                )
+                .long("streaming-diff"),
+        )
+        .arg(
+            Arg::with_name("streaming-diff-above")
+                .help(
+                    "Use the streaming diff when either side of a diff has more than this many \
+                     lines",
+                )
+                .long("streaming-diff-above")
+                .takes_value(true)
+                .value_name("LINES")
+                .default_value("5000")
+                .validator(|n| {
+                    n.parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|_| String::from("expected a number of lines"))
+                }),
+        )
+        .arg(
+            Arg::with_name("max-diffs")
+                .help(
+                    "Only print the diffs of the first N tasks that differ from the wiki, and \
+                     count the rest. 0 prints every diff.",
+                )
+                .long("max-diffs")
+                .takes_value(true)
+                .value_name("N")
+                .validator(|n| {
+                    n.parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|_| String::from("expected a number of tasks"))
+                }),
+        )
+        .arg(
+            Arg::with_name("ignore-whitespace")
+                .help(
+                    "Don't consider differences in whitespace, such as trailing whitespace or \
+                     indentation, when comparing tasks",
+                )
+                .long("ignore-whitespace"),
+        )
+        .arg(
+            Arg::with_name("no-wrap")
+                .help("Don't wrap long titles and lines of code to the width of the terminal")
+                .long("no-wrap"),
+        )
+        .arg(
+            Arg::with_name("no-color")
+                .help("Print plain text without colors, even if stdout is a terminal")
+                .long("no-color"),
+        )
+        .arg(
+            Arg::with_name("tab-width")
+                .help("The number of columns between tab stops when printing code")
+                .long("tab-width")
+                .takes_value(true)
+                .default_value("4")
+                .validator(|width| match width.parse::<usize>() {
+                    Ok(width) if width > 0 => Ok(()),
+                    _ => Err(String::from("the tab width must be a positive integer")),
+                }),
+        )
+        .arg(
+            Arg::with_name("include-description")
+                .help(
+                    "Include the description of each task on the wiki, in the JSON output and \
+                     shortened in the terminal",
+                )
+                .long("include-description"),
+        )
+        .arg(
+            Arg::with_name("show-edit-url")
+                .help("Print the URL that edits the Rust section of each task on the wiki")
+                .long("show-edit-url"),
+        )
+        .arg(
+            Arg::with_name("show-languages")
+                .help("Print the languages that each task is implemented in on the wiki")
+                .long("show-languages"),
+        )
+        .arg(
+            Arg::with_name("show-sizes")
+                .help("Print the size in bytes of the local and remote code of each task")
+                .long("show-sizes"),
+        )
+        .arg(
+            Arg::with_name("missing-deps")
+                .help(
+                    "Report crates used by the local code of a task that are not declared as \
+                     dependencies in its manifest",
+                )
+                .long("missing-deps"),
+        )
+        .arg(
+            Arg::with_name("check-main")
+                .help("Report local implementations of tasks that don't declare a `fn main`")
+                .long("check-main"),
+        )
+        .arg(
+            Arg::with_name("check-naming")
+                .help(
+                    "Report local tasks whose directories don't match their titles on the wiki, \
+                     and suggest how to rename them. Omitted tasks are not checked.",
+                )
+                .long("check-naming"),
+        )
+        .arg(
+            Arg::with_name("todos")
+                .help(
+                    "Report `// TODO` and `// FIXME` comments and `unimplemented!()` or `todo!()` \
+                     macros in the code of local tasks, with their line numbers",
+                )
+                .long("todos"),
+        )
+        .arg(
+            Arg::with_name("fail-on-match")
+                .help(
+                    "Exit with 1 if --todos finds any markers, or if --require-remote finds any \
+                     tasks",
+                )
+                .long("fail-on-match"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Log details about the requests to the wiki, such as corrected titles")
+                .long("verbose"),
+        )
+        .arg(
+            Arg::with_name("suggest-renames")
+                .help(
+                    "Print the `git mv` commands that move the local tasks whose pages were \
+                     renamed on the wiki to the directories of their new titles. Nothing is moved.",
+                )
+                .long("suggest-renames"),
+        )
+        .arg(
+            Arg::with_name("explain")
+                .help("Print which tasks are omitted from the listing, and why")
+                .long("explain"),
+        )
+        .arg(
+            Arg::with_name("list-values")
+                .help("Print the values accepted by an option, one per line, and exit")
+                .long("list-values")
+                .takes_value(true)
+                .value_name("OPTION")
+                .possible_values(&[
+                    "columns",
+                    "filter",
+                    "format",
+                    "group-by",
+                    "json-fields",
+                    "module",
+                    "sort",
+                    "url-style",
+                ]),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .help(
+                    "The order to print the tasks in. `alpha` ignores case and accents, the same \
+                     way on every system.",
+                )
+                .long("sort")
+                .possible_values(SortOrder::VALUES)
+                .default_value("alpha"),
+        )
+        .arg(
+            Arg::with_name("url-style")
+                .help("How to display task URLs")
+                .possible_values(UrlStyle::VALUES)
+                .long("url-style")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("filter")
+                .help(
+                    "Filter tasks printed by the program. `diff` selects tasks that are \
+                     implemented both locally and remotely, but differ.",
+                )
+                .possible_values(FILTERS)
+                .long("filter")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("min-languages")
+                .help(
+                    "Only include tasks that are implemented in at least this many languages on \
+                     the wiki. Combine with `--filter unimplemented` to find popular tasks that \
+                     are missing.",
+                )
+                .long("min-languages")
+                .takes_value(true)
+                .value_name("N")
+                .validator(|n| {
+                    n.parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|_| String::from("expected a number of languages"))
+                }),
+        )
+        .arg(
+            Arg::with_name("since-revision")
+                .help(
+                    "Print the tasks that changed on the wiki since the revisions stored in this \
+                     file by `--save-revisions`, without downloading their code. Exits with 1 if \
+                     any task changed.",
+                )
+                .long("since-revision")
+                .takes_value(true)
+                .value_name("FILE")
+                .conflicts_with_all(&["snapshot", "revisions"]),
+        )
+        .arg(
+            Arg::with_name("diff-against")
+                .help(
+                    "Compare local tasks against the latest code on the wiki, the remote code in \
+                     a report written by `--json-file`, or the revisions stored by \
+                     `--save-revisions`. Tasks that are missing from the snapshot or the \
+                     revisions count as unimplemented on the wiki.",
+                )
+                .long("diff-against")
+                .takes_value(true)
+                .possible_values(DIFF_AGAINST)
+                .default_value("latest"),
+        )
+        .arg(
+            Arg::with_name("snapshot")
+                .help("The report to compare against with `--diff-against snapshot`")
+                .long("snapshot")
+                .takes_value(true)
+                .value_name("FILE")
+                .required_if("diff-against", "snapshot"),
+        )
+        .arg(
+            Arg::with_name("revisions")
+                .help("The revisions to compare against with `--diff-against revision`")
+                .long("revisions")
+                .takes_value(true)
+                .value_name("FILE")
+                .required_if("diff-against", "revision"),
+        )
+        .arg(
+            Arg::with_name("save-revisions")
+                .help(
+                    "Store the latest revision of each task on the wiki in this file, for a later \
+                     `--since-revision`",
+                )
+                .long("save-revisions")
+                .takes_value(true)
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("only-changed-checksums")
+                .help(
+                    "Only include tasks whose code on the wiki changed since an earlier JSON \
+                     report, written with the `checksums` field, or that are missing from it",
+                )
+                .long("only-changed-checksums")
+                .takes_value(true)
+                .value_name("REPORT"),
+        )
+        .arg(
+            Arg::with_name("min-ratio")
+                .help(
+                    "Only include tasks whose local and remote code are at least this similar, \
+                     from 0 to 1",
+                )
+                .long("min-ratio")
+                .takes_value(true)
+                .value_name("RATIO")
+                .validator(|ratio| parse_ratio(&ratio).map(|_| ())),
+        )
+        .arg(
+            Arg::with_name("max-ratio")
+                .help(
+                    "Only include tasks whose local and remote code are at most this similar, \
+                     from 0 to 1",
+                )
+                .long("max-ratio")
+                .takes_value(true)
+                .value_name("RATIO")
+                .validator(|ratio| parse_ratio(&ratio).map(|_| ())),
+        )
+        .arg(
+            Arg::with_name("require-remote")
+                .help(
+                    "Report an error for every task that is implemented locally but has no Rust \
+                     code on the wiki, such as tasks whose section was removed. Exits with 1 if \
+                     there are any and --fail-on-match is given.",
+                )
+                .long("require-remote"),
+        )
+        .arg(
+            Arg::with_name("fail-on-parse-error")
+                .help(
+                    "Report a parse error and exit with 1 for every task whose page has a Rust \
+                     section that no code could be extracted from. Tasks without a Rust section \
+                     are not errors.",
+                )
+                .long("fail-on-parse-error"),
+        )
+        .arg(
+            Arg::with_name("fail-under")
+                .help(
+                    "Exit with an error if less than this percentage of the tasks is implemented \
+                     locally. Omitted tasks are not counted.",
+                )
+                .long("fail-under")
+                .takes_value(true)
+                .value_name("PERCENT")
+                .validator(|percent| match percent.parse::<f64>() {
+                    Ok(percent) if percent >= 0.0 && percent <= 100.0 => Ok(()),
+                    _ => Err(String::from("expected a percentage from 0 to 100")),
+                }),
+        )
+        .arg(
+            Arg::with_name("first")
+                .help("Stop after this many tasks have matched the filter")
+                .long("first")
+                .takes_value(true)
+                .value_name("N")
+                .validator(|n| {
+                    n.parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|_| String::from("expected a number of tasks"))
+                }),
+        )
+        .arg(
+            Arg::with_name("module")
+                .help(
+                    "Only include the local tasks under tasks/<MODULE>. Run with \
+                     `--list-values module` to see the available modules.",
+                )
+                .long("module")
+                .takes_value(true)
+                .value_name("MODULE"),
+        )
+        .arg(
+            Arg::with_name("max-tasks-per-category")
+                .help(
+                    "Only process this many tasks from each category on the wiki. Tasks are \
+                     picked alphabetically, or at random if a seed is given.",
+                )
+                .long("max-tasks-per-category")
+                .takes_value(true)
+                .value_name("N")
+                .validator(|n| {
+                    n.parse::<usize>()
+                        .map(|_| ())
+                        .map_err(|_| String::from("expected a number of tasks"))
+                }),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .help("Pick tasks at random with this seed, for --max-tasks-per-category")
+                .long("seed")
+                .takes_value(true)
+                .requires("max-tasks-per-category")
+                .validator(|n| {
+                    n.parse::<u64>()
+                        .map(|_| ())
+                        .map_err(|_| String::from("expected a number"))
+                }),
+        )
+        .arg(
+            Arg::with_name("deadline")
+                .help(
+                    "Stop fetching tasks after this long, such as '90s', '5m' or '2h'. Tasks \
+                     that were processed so far are still reported.",
+                )
+                .long("deadline")
+                .takes_value(true)
+                .value_name("DURATION")
+                .validator(|s| parse_duration(&s).map(|_| ())),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help("How to print the status of each task")
+                .long("format")
+                .possible_values(FORMATS)
+                .default_value("full"),
+        )
+        .arg(
+            Arg::with_name("group-by")
+                .help(
+                    "Print the titles of the tasks in sections by their implementation status, \
+                     instead of the status of each task",
+                )
+                .long("group-by")
+                .possible_values(GROUPINGS)
+                .conflicts_with("heatmap"),
+        )
+        .arg(
+            Arg::with_name("heatmap")
+                .help(
+                    "Print a grid of how many tasks are implemented locally, grouped by the first \
+                     letter of their titles, instead of the status of each task",
+                )
+                .long("heatmap"),
+        )
+        .arg(
+            Arg::with_name("summary")
+                .help(
+                    "Print the number of tasks in each status and the percentage of tasks that \
+                     are implemented locally. Also included in the JSON output.",
+                )
+                .long("summary"),
+        )
+        .arg(
+            Arg::with_name("json-file")
+                .help(
+                    "Dump json to the provided filename, or to stdout if the filename is '-'. \
+                     Filenames ending in `.gz` are compressed with gzip. If some tasks can't be \
+                     fetched, a JSON object with an `errors` array is printed to stderr, and the \
+                     exit code is 1.",
+                )
+                .long("json")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("json-compact")
+                .help("Write the JSON output on a single line instead of pretty-printing it")
+                .long("json-compact"),
+        )
+        .arg(
+            Arg::with_name("relative-paths")
+                .help(
+                    "Write the paths of the tasks in the JSON output relative to the manifest \
+                     directory that is being checked, instead of the checkout that the tool was \
+                     built from",
+                )
+                .long("relative-paths"),
+        )
+        .arg(
+            Arg::with_name("json-fields")
+                .help(
+                    "Comma-separated list of the fields to include in the JSON output. Defaults \
+                     to all fields, except the description without --include-description.",
+                )
+                .long("json-fields")
+                .takes_value(true)
+                .value_name("FIELDS")
+                .validator(|fields| {
+                    match fields.split(',').find(|field| !JSON_FIELDS.contains(field)) {
+                        Some(field) => Err(format!(
+                            "unknown field '{}', expected some of {}",
+                            field,
+                            JSON_FIELDS.join(", ")
+                        )),
+                        None => Ok(()),
+                    }
+                }),
+        )
+        .arg(
+            Arg::with_name("columns")
+                .help(
+                    "Print the status of each task as a row of a table, with these \
+                     comma-separated columns",
+                )
+                .long("columns")
+                .takes_value(true)
+                .value_name("COLUMNS")
+                .validator(|columns| {
+                    match columns.split(',').find(|column| !COLUMNS.contains(column)) {
+                        Some(column) => Err(format!(
+                            "unknown column '{}', expected some of {}",
+                            column,
+                            COLUMNS.join(", ")
+                        )),
+                        None => Ok(()),
+                    }
+                }),
+        )
+        .arg(
+            Arg::with_name("emit-patches")
+                .help(
+                    "Write a unified diff from the wiki to the local code of each differing task \
+                     into this directory",
+                )
+                .long("emit-patches")
+                .takes_value(true)
+                .value_name("DIR"),
+        )
+        .arg(
+            Arg::with_name("dump-remote")
+                .help(
+                    "Write the code of each task on the wiki into this directory, one file per \
+                     task named after its title. Tasks without code on the wiki are skipped.",
+                )
+                .long("dump-remote")
+                .takes_value(true)
+                .value_name("DIR"),
+        )
+        .arg(
+            Arg::with_name("overwrite")
+                .help("Replace the files that already exist in the directory of --dump-remote")
+                .long("overwrite")
+                .requires("dump-remote"),
+        )
+        .arg(
+            Arg::with_name("watch")
+                .help(
+                    "Print the diffs of the given tasks again whenever their local code changes. \
+                     The tasks are only fetched from the wiki once.",
+                )
+                .long("watch")
+                .requires("task"),
+        )
+        .arg(
+            Arg::with_name("http-cache")
+                .help(
+                    "Store responses from the wiki under target/http-cache, and only download \
+                     pages again if they changed or are older than an hour",
+                )
+                .long("http-cache"),
+        )
+        .arg(
+            Arg::with_name("revision-cache")
+                .help(
+                    "Store the pages of tasks under target/revision-cache, and only download them \
+                     again once they have a newer revision on the wiki",
+                )
+                .long("revision-cache"),
+        )
+        .arg(
+            Arg::with_name("concurrency")
+                .help(
+                    "The maximum number of requests that are sent to the wiki at the same time. \
+                     Defaults to 2. The tasks are currently fetched one request at a time, so \
+                     this has no effect yet.",
+                )
+                .long("concurrency")
+                .takes_value(true)
+                .value_name("N")
+                .validator(|n| match n.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(()),
+                    _ => Err(String::from("expected a positive number of requests")),
+                }),
+        )
+        .arg(
+            Arg::with_name("wiki-url")
+                .help(
+                    "The URL of the API of the wiki (its api.php), to use a private or \
+                     self-hosted MediaWiki instead of Rosetta Code",
+                )
+                .long("wiki-url")
+                .takes_value(true)
+                .value_name("URL")
+                .validator(|url| {
+                    Url::parse(&url)
+                        .map(|_| ())
+                        .map_err(|err| format!("invalid URL: {}", err))
+                }),
+        )
+        .arg(
+            Arg::with_name("wiki-user")
+                .help(
+                    "Log in to the wiki as this user, for wikis that require an account. The \
+                     password is read from ROSETTA_WIKI_PASSWORD, or from stdin if it is not \
+                     set.",
+                )
+                .long("wiki-user")
+                .takes_value(true)
+                .value_name("USER"),
+        )
+        .arg(
+            Arg::with_name("rebuild-index")
+                .help("Scan the repository for tasks even if nothing changed since the last run")
+                .long("rebuild-index"),
+        )
+        .subcommand(
+            SubCommand::with_name("diff-local")
+                .about(
+                    "Compare the local code of each task against another checkout of the \
+                     repository, without accessing the wiki",
+                )
+                .arg(
+                    Arg::with_name("other")
+                        .help("The directory containing the Cargo.toml of the other checkout")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("validate")
+                .about(
+                    "Check that a JSON report written by --json matches the schema of the \
+                     current version",
+                )
+                .arg(
+                    Arg::with_name("report")
+                        .help("The JSON report to check")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about(
+                    "Print histograms of the sizes of the local code and of the diffs, and the \
+                     number of implemented tasks in each category",
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .help("How to print the statistics")
+                        .long("format")
+                        .possible_values(&["text", "json"])
+                        .default_value("text"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("audit")
+                .about(
+                    "Compare the local tasks against every task on the wiki, listing the tasks \
+                     that are not implemented and the local tasks that are no longer on the wiki",
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .help("How to print the report")
+                        .long("format")
+                        .possible_values(&["term", "json", "markdown"])
+                        .default_value("term"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("check")
+                .about(
+                    "Run several validations of the repository in one pass, and exit with 1 if \
+                     any of them fails",
+                )
+                .after_help(CHECKS_HELP)
+                .arg(
+                    Arg::with_name("skip")
+                        .help("Comma-separated list of the checks not to run")
+                        .long("skip")
+                        .takes_value(true)
+                        .value_name("CHECKS")
+                        .validator(validate_checks),
+                )
+                .arg(
+                    Arg::with_name("only")
+                        .help("Comma-separated list of the checks to run. Defaults to all checks.")
+                        .long("only")
+                        .takes_value(true)
+                        .value_name("CHECKS")
+                        .conflicts_with("skip")
+                        .validator(validate_checks),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff-file")
+                .about(
+                    "Compare a file against the code of a task on the wiki, instead of the local \
+                     implementation of the task",
+                )
+                .arg(
+                    Arg::with_name("task")
+                        .help("The name of a task on the wiki, such as 'K-d tree'")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("path")
+                        .help("The file to compare")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff-url")
+                .about(
+                    "Compare the code at a URL, such as a raw gist, against the code of a task on \
+                     the wiki and in the repository",
+                )
+                .arg(
+                    Arg::with_name("task")
+                        .help("The name of a task on the wiki, such as 'K-d tree'")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("url")
+                        .help("The URL of the plain code to compare")
+                        .required(true)
+                        .validator(|url| {
+                            Url::parse(&url)
+                                .map(|_| ())
+                                .map_err(|err| format!("invalid URL: {}", err))
+                        }),
+                )
+                .arg(
+                    Arg::with_name("against")
+                        .help("Which code of the task to compare against")
+                        .long("against")
+                        .takes_value(true)
+                        .possible_values(&["wiki", "local", "both"])
+                        .default_value("both"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("open")
+                .about("Open the page of a task on the wiki in the default browser")
+                .arg(
+                    Arg::with_name("task")
+                        .help("The name of a task on the wiki, such as 'K-d tree'")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("edit")
+                        .help("Open the page that edits the Rust section of the task instead")
+                        .long("edit"),
+                )
+                .arg(
+                    Arg::with_name("local")
+                        .help("Open the local source code of the task in $EDITOR instead")
+                        .long("local")
+                        .conflicts_with("edit"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("scaffold")
+                .about("Create a new crate for a task that is not implemented locally")
+                .arg(
+                    Arg::with_name("task")
+                        .help("The name of a task on the wiki, such as 'K-d tree'")
+                        .required(true),
+                ),
+        )
+        .get_matches();
+
+    // Warnings of the library, such as unreadable files, are always shown. `RUST_LOG` still
+    // applies, so more detailed logs can be requested as well.
+    let mut logger = env_logger::LogBuilder::new();
+    if matches.is_present("verbose") {
+        logger.filter(Some("meta"), log::LogLevelFilter::Info);
+    } else {
+        logger.filter(Some("meta"), log::LogLevelFilter::Warn);
+    }
+    if let Ok(filters) = env::var("RUST_LOG") {
+        logger.parse(&filters);
+    }
+    logger.init().unwrap();
+
+    diff::set_streaming_threshold(if matches.is_present("streaming-diff") {
+        Some(0)
+    } else {
+        value_t!(matches.value_of("streaming-diff-above"), usize).ok()
+    });
+
+    let color = !matches.is_present("no-color");
+    let mut t = stdout_console(color);
+
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+
+    if let Some(option) = matches.value_of("list-values") {
+        // The modules depend on the repository, so they are read from the local tasks.
+        if option == "module" {
+            let tasks = match local::parse_tasks(Path::new(manifest_dir).join("Cargo.toml")) {
+                Ok(tasks) => tasks,
+                Err(err) => {
+                    eprintln!("Could not read tasks under {}: {}", manifest_dir, err);
+                    process::exit(2);
+                }
+            };
+
+            let modules = tasks
+                .iter()
+                .filter_map(|task| local::module(task, Path::new(manifest_dir)))
+                .collect::<BTreeSet<_>>();
+            for module in modules {
+                println!("{}", module);
+            }
+        } else {
+            for value in option_values(option) {
+                println!("{}", value);
+            }
+        }
+        return;
+    }
+
+    let mut requested_tasks = vec![];
+    let mut read_stdin = false;
+    if let Some(tasks) = matches.values_of("task") {
+        for title in tasks {
+            if title == "-" {
+                read_stdin = true;
+            } else {
+                requested_tasks.push(title.to_owned());
+            }
+        }
+    }
+
+    // Only read stdin implicitly if it's piped, otherwise the program would wait for input. The
+    // subcommands don't take task names.
+    let piped = !atty::is(atty::Stream::Stdin) && matches.subcommand_name().is_none();
+    if read_stdin || (requested_tasks.is_empty() && piped) {
+        // Without the environment variable, the password would be read from stdin as well.
+        if matches.is_present("wiki-user") && env::var(WIKI_PASSWORD_VAR).is_err() {
+            eprintln!(
+                "Task names are read from stdin, so set {} to log in to the wiki",
+                WIKI_PASSWORD_VAR
+            );
+            process::exit(2);
+        }
+
+        let stdin = io::stdin();
+        match read_titles(stdin.lock()) {
+            Ok(titles) => requested_tasks.extend(titles),
+            Err(err) => {
+                eprintln!("Could not read task names from stdin: {}", err);
+                process::exit(1);
+            }
+        }
+    }
+
+    let requested_tasks = if requested_tasks.is_empty() {
+        None
+    } else {
+        Some(requested_tasks)
+    };
+
+    let filter = value_t!(matches.value_of("filter"), Filter)
+        .ok()
+        .unwrap_or_default();
+
+    let url_style = value_t!(matches.value_of("url-style"), UrlStyle).ok();
+
+    let print_options = PrintOptions {
+        diff: matches.is_present("diff") || matches.is_present("watch"),
+        classify: matches.is_present("classify"),
+        detect_moves: matches.is_present("detect-moves"),
+        skip_identical_diffs: matches.is_present("no-diff-context-for-identical"),
+        sizes: matches.is_present("show-sizes"),
+        languages: matches.is_present("show-languages"),
+        description: matches.is_present("include-description"),
+        edit_url: matches.is_present("show-edit-url"),
+        ignore_whitespace: matches.is_present("ignore-whitespace"),
+        url_style,
+        wrap_width: if matches.is_present("no-wrap") {
+            None
+        } else {
+            term_size::dimensions().map(|(width, _)| width)
+        },
+        tab_width: value_t!(matches.value_of("tab-width"), usize).unwrap(),
+    };
+
+    if let Some(matches) = matches.subcommand_matches("validate") {
+        let path = matches.value_of("report").unwrap();
+
+        let json = match report::read_report(path) {
+            Ok(json) => json,
+            Err(err) => {
+                eprintln!("Could not read {}: {}", path, err);
+                process::exit(1);
+            }
+        };
+
+        match CoverageReport::from_json(&json) {
+            Ok(report) => println!(
+                "{} matches the current schema ({} tasks{})",
+                path,
+                report.tasks.len(),
+                if report.summary.is_some() {
+                    ", with summary"
+                } else {
+                    ""
+                }
+            ),
+            Err(err) => {
+                eprintln!("{} does not match the current schema: {}", path, err);
+                process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    if let Some(sub_matches) = matches.subcommand_matches("diff-local") {
+        let parse = |dir: &Path| match local::parse_tasks(dir.join("Cargo.toml")) {
+            Ok(tasks) => tasks,
+            Err(err) => {
+                eprintln!("Could not read tasks under {}: {}", dir.display(), err);
+                process::exit(2);
+            }
+        };
+
+        let ours = parse(Path::new(manifest_dir));
+        let theirs = parse(Path::new(sub_matches.value_of("other").unwrap()));
+        let changes = local_changes(&ours, &theirs);
+
+        // As in the main mode, JSON on stdout replaces the human-readable output.
+        if matches.value_of("json-file") != Some("-") {
+            for change in &changes {
+                print_local_change(&mut *t, change, &print_options).unwrap();
+            }
+        }
+
+        if let Some(filename) = matches.value_of("json-file") {
+            let json = changes
+                .iter()
+                .map(|change| {
+                    json!({
+                        "title": change.title,
+                        "change": change.kind.name(),
+                        "path": change.path,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            write_json(filename, &json!(json), matches.is_present("json-compact")).unwrap();
+        }
+
+        return;
+    }
+
+    // Scanning the repository is slow, so the local tasks are cached between runs.
+    let cache_path = Path::new(manifest_dir)
+        .join("target")
+        .join("coverage-index.json");
+
+    if matches.is_present("rebuild-index") && cache_path.exists() {
+        fs::remove_file(&cache_path).unwrap();
+    }
+
+    let mut http_client = HttpClient::new();
+
+    if let Ok(concurrency) = value_t!(matches.value_of("concurrency"), usize) {
+        http_client = http_client.with_concurrency(concurrency);
+    }
+
+    if let Some(url) = matches.value_of("wiki-url") {
+        http_client = http_client.with_api_url(Url::parse(url).unwrap());
+    }
+
+    if let Some(user) = matches.value_of("wiki-user") {
+        let password = match wiki_password() {
+            Ok(password) => password,
+            Err(err) => {
+                eprintln!("could not read the password of the wiki: {}", err);
+                process::exit(2);
+            }
+        };
+
+        if let Err(err) = http_client.login(user, &password) {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    }
+
+    // The caching client is shared with the index, so that its statistics can be printed at the
+    // end.
+    let (client, caching_client): (Box<WikiClient>, _) = if matches.is_present("http-cache") {
+        let caching_client = Rc::new(CachingClient::new(
+            http_client,
+            Path::new(manifest_dir).join("target").join("http-cache"),
+            Duration::from_secs(60 * 60),
+        ));
+        (Box::new(caching_client.clone()), Some(caching_client))
+    } else {
+        (Box::new(http_client), None)
+    };
+
+    // Local tasks that are missing on the wiki would fail to create the index, so the audit reads
+    // the local tasks itself.
+    if let Some(matches) = matches.subcommand_matches("audit") {
+        let (local_titles, omitted_titles) = match read_local_titles(manifest_dir, &cache_path) {
+            Ok(titles) => titles,
+            Err(err) => {
+                eprintln!("Could not read tasks under {}: {}", manifest_dir, err);
+                process::exit(2);
+            }
+        };
+
+        let audit = match audit::audit(&*client, &local_titles, &omitted_titles) {
+            Ok(audit) => audit,
+            Err(err) => {
+                eprintln!("Could not fetch the tasks on the wiki: {}", err);
+                process::exit(2);
+            }
+        };
+
+        match matches.value_of("format") {
+            Some("json") => println!("{}", serde_json::to_string_pretty(&audit).unwrap()),
+            Some("markdown") => print!("{}", audit_markdown(&audit)),
+            _ => print_audit(&mut *t, &audit).unwrap(),
+        }
+
+        if !audit.is_empty() {
+            process::exit(1);
+        }
+
+        return;
+    }
+
+    // Like the audit, the checks read the local tasks themselves, since some of the checks find
+    // problems that would fail to create the index.
+    if let Some(matches) = matches.subcommand_matches("check") {
+        let enabled = match (matches.value_of("only"), matches.value_of("skip")) {
+            (Some(only), _) => only.split(',').collect::<Vec<_>>(),
+            (None, Some(skip)) => {
+                let skipped = skip.split(',').collect::<Vec<_>>();
+                CHECKS
+                    .iter()
+                    .cloned()
+                    .filter(|check| !skipped.contains(check))
+                    .collect()
+            }
+            (None, None) => CHECKS.to_vec(),
+        };
+
+        let results = run_checks(&enabled, manifest_dir, &cache_path, client);
+        print_check_results(&mut *t, &results).unwrap();
+
+        if results.iter().any(|result| !result.problems.is_empty()) {
+            process::exit(1);
+        }
+
+        return;
+    }
+
+    // The old titles of renamed tasks would fail to create the index as well.
+    if matches.is_present("suggest-renames") {
+        let manifest_path = Path::new(manifest_dir).join("Cargo.toml");
+        let tasks = local::ScanOptions::for_workspace(Path::new(manifest_dir))
+            .and_then(|options| local::parse_tasks_cached(&manifest_path, &cache_path, &options));
+
+        let tasks = match tasks {
+            Ok(tasks) => tasks,
+            Err(err) => {
+                eprintln!("Could not read tasks under {}: {}", manifest_dir, err);
+                process::exit(2);
+            }
+        };
+
+        let renames = match audit::renames(&*client, &tasks, Path::new(manifest_dir)) {
+            Ok(renames) => renames,
+            Err(err) => {
+                eprintln!("Could not fetch the redirects of the tasks: {}", err);
+                process::exit(2);
+            }
+        };
+
+        print!("{}", rename_commands(&renames, Path::new(manifest_dir)));
+
+        if !renames.is_empty() {
+            eprintln!(
+                "Afterwards, update the URL in the Cargo.toml of each task and the paths in \
+                 `workspace.members`."
+            );
+        }
+
+        return;
+    }
+
+    let mut builder = TaskIndex::builder(manifest_dir)
+        .index_cache(&cache_path)
+        .client(client);
+    if matches.is_present("revision-cache") {
+        // Pages are stored by title, so other wikis and logged in users get a directory of their
+        // own.
+        let mut dir = Path::new(manifest_dir)
+            .join("target")
+            .join("revision-cache");
+        if let Some(url) = matches.value_of("wiki-url") {
+            dir.push(slugify(url));
+        }
+        if let Some(user) = matches.value_of("wiki-user") {
+            dir.push(format!("user-{}", slugify(user)));
+        }
+        builder = builder.revision_cache(dir);
+    }
+    let task_index = builder.build();
+
+    let task_index = match task_index {
+        Ok(task_index) => task_index,
+        Err(err) => {
+            eprintln!(
+                "Could not locate tasks directory under {}: {}",
+                manifest_dir, err
+            );
+            process::exit(2);
+        }
+    };
+
+    if matches.is_present("verbose") {
+        match task_index.ignored_files() {
+            Ok(0) => {}
+            Ok(count) => eprintln!("Ignored {} files matching {}", count, local::IGNORE_FILE),
+            Err(err) => eprintln!("warning: could not count ignored files: {}", err),
+        }
+    }
+
+    for title in task_index.omitted_titles() {
+        if task_index.is_implemented_locally(title) {
+            eprintln!(
+                "warning: '{}' is implemented locally, but is omitted in Cargo.toml",
+                title
+            );
+        }
+    }
+
+    // Aliases are resolved here as well as by `fetch_tasks`, so that the requested titles can be
+    // compared against the titles of the index.
+    let mut applied_aliases = vec![];
+    let requested_tasks = requested_tasks.map(|tasks| {
+        tasks
+            .into_iter()
+            .map(|name| {
+                let title = task_index.resolve_alias(&name).to_owned();
+                if title != name {
+                    applied_aliases.push((name, title.clone()));
+                }
+                title
+            })
+            .collect::<Vec<_>>()
+    });
+
+    if let Some(ref tasks) = requested_tasks {
+        for title in tasks.iter().filter(|title| task_index.is_omitted(title)) {
+            eprintln!(
+                "warning: '{}' is omitted in Cargo.toml, but is included because it was requested \
+                 by name",
+                title
+            );
+        }
+    }
+
+    if matches.is_present("explain") {
+        print_omitted(&mut *t, &task_index).unwrap();
+        print_aliases(&mut *t, &applied_aliases).unwrap();
+    }
+
+    if matches.is_present("check-naming") {
+        let mismatches = task_index.naming_mismatches();
+
+        for mismatch in &mismatches {
+            print_naming_mismatch(&mut *t, mismatch).unwrap();
+        }
+
+        if !mismatches.is_empty() {
+            process::exit(1);
+        }
+
+        return;
+    }
+
+    if matches.is_present("todos") {
+        let todos = task_index.todo_markers();
+
+        for (title, markers) in &todos {
+            print_todos(&mut *t, title, markers, Path::new(manifest_dir)).unwrap();
+        }
+
+        if matches.is_present("fail-on-match") && !todos.is_empty() {
+            process::exit(1);
+        }
+
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("stats") {
+        let json = matches.value_of("format") == Some("json");
+        print_stats(&mut *t, &task_index, json).unwrap();
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("diff-file") {
+        let title = matches.value_of("task").unwrap();
+        let path = matches.value_of("path").unwrap();
+
+        let mut code = String::new();
+        if let Err(err) = File::open(path).and_then(|mut file| file.read_to_string(&mut code)) {
+            eprintln!("Could not read {}: {}", path, err);
+            process::exit(1);
+        }
+
+        let remote_code = match task_index.fetch_tasks(&[title.to_owned()]).next() {
+            Some(Ok(task)) => task.remote_code(),
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                process::exit(2);
+            }
+            None => {
+                eprintln!("Could not find a task named '{}' on the wiki", title);
+                process::exit(1);
+            }
+        };
+
+        let remote_code = match remote_code {
+            Some(remote_code) => remote_code,
+            None => {
+                eprintln!("'{}' is not implemented in Rust on the wiki", title);
+                process::exit(1);
+            }
+        };
+
+        print_code_diff(&mut *t, &remote_code, &code, &print_options).unwrap();
+
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("diff-url") {
+        let title = task_index.resolve_alias(matches.value_of("task").unwrap());
+        let url = matches.value_of("url").unwrap();
+
+        // A separate client, so that the session of the wiki is never sent to another site.
+        let code = match HttpClient::new().get(&Url::parse(url).unwrap()) {
+            Ok(code) => code,
+            Err(err) => {
+                eprintln!("Could not fetch the code at {}: {}", url, err);
+                process::exit(1);
+            }
+        };
+
+        let task = match task_index.fetch_tasks(&[title.to_owned()]).next() {
+            Some(Ok(task)) => task,
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                process::exit(2);
+            }
+            None => {
+                eprintln!("Could not find a task named '{}' on the wiki", title);
+                process::exit(1);
+            }
+        };
+
+        let against = matches.value_of("against").unwrap();
+        let bases = [("wiki", task.remote_code()), ("local", task.local_code())];
+
+        for &(name, ref base) in bases
+            .iter()
+            .filter(|&&(name, _)| against == "both" || against == name)
+        {
+            match *base {
+                Some(ref base) => {
+                    t.attr(term::Attr::Bold).unwrap();
+                    writeln!(t, "Compared to the {} code", name).unwrap();
+                    t.reset().unwrap();
+                    print_code_diff(&mut *t, base, &code, &print_options).unwrap();
+                }
+                None => eprintln!("'{}' has no {} code to compare to", title, name),
+            }
+        }
+
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("open") {
+        let title = task_index.resolve_alias(matches.value_of("task").unwrap());
+
+        let task = match task_index.fetch_tasks(&[title.to_owned()]).next() {
+            Some(Ok(task)) => task,
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                process::exit(2);
+            }
+            None => {
+                eprintln!("Could not find a task named '{}' on the wiki", title);
+                process::exit(1);
+            }
+        };
+
+        if matches.is_present("local") {
+            let path = match task.local_source_path() {
+                Some(path) => path,
+                None => {
+                    eprintln!("'{}' is not implemented locally", title);
+                    process::exit(1);
+                }
+            };
+
+            // Without an editor, print the path so that it can be opened by hand.
+            let opened = env::var("EDITOR")
+                .ok()
+                .and_then(|editor| process::Command::new(editor).arg(&path).status().ok())
+                .map_or(false, |status| status.success());
+            if !opened {
+                println!("{}", path.display());
+            }
+        } else {
+            let url = if matches.is_present("edit") {
+                task.edit_url()
+            } else {
+                task.url()
+            };
+
+            // Without a browser, print the URL so that it can be opened by hand.
+            if webbrowser::open(url.as_str()).is_err() {
+                println!("{}", url);
+            }
+        }
+
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("scaffold") {
+        let title = matches.value_of("task").unwrap();
+
+        let task = match task_index.fetch_tasks(&[title.to_owned()]).next() {
+            Some(Ok(task)) => task,
+            Some(Err(err)) => {
+                eprintln!("{}", err);
+                process::exit(2);
+            }
+            None => {
+                eprintln!("Could not find a task named '{}' on the wiki", title);
+                process::exit(1);
+            }
+        };
+
+        if let Some(path) = task.local_path() {
+            eprintln!("'{}' is already implemented in {}", title, path.display());
+            process::exit(1);
+        }
+
+        match scaffold(&task) {
+            Ok(path) => println!(
+                "Created {} and added it to `workspace.members` in Cargo.toml",
+                path.display()
+            ),
+            Err(err) => {
+                eprintln!("Could not scaffold '{}': {}", title, err);
+                process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    // Without a filter, the heatmap only needs to know which tasks are implemented locally, so it
+    // can be printed without fetching anything from the wiki.
+    let unfiltered = match filter {
+        Filter::All => true,
+        _ => false,
+    };
+
+    if matches.is_present("heatmap") && unfiltered {
+        let mut heatmap = Heatmap::default();
+        for title in task_index.all_titles() {
+            let included = requested_tasks
+                .as_ref()
+                .map_or(true, |tasks| tasks.iter().any(|task| task == title));
+
+            if included {
+                heatmap.add(title, task_index.is_implemented_locally(title));
+            }
+        }
+
+        print_heatmap(&mut *t, &heatmap).unwrap();
+        return;
+    }
+
+    let mut task_names = requested_tasks;
+
+    if let Some(module) = matches.value_of("module") {
+        let modules = task_index.modules();
+        let titles = match modules.get(module) {
+            Some(titles) => titles,
+            None => {
+                eprintln!("There is no module named '{}'", module);
+                process::exit(1);
+            }
+        };
+
+        let selected: Vec<String> = match task_names {
+            Some(ref names) => names
+                .iter()
+                .filter(|name| titles.contains(*name))
+                .cloned()
+                .collect(),
+            None => titles.clone(),
+        };
+
+        // An empty list would fetch every task.
+        if selected.is_empty() {
+            eprintln!("None of the requested tasks are in module '{}'.", module);
+            return;
+        }
+
+        task_names = Some(selected);
+    }
+
+    if let Ok(max) = value_t!(matches.value_of("max-tasks-per-category"), usize) {
+        let sampling = match value_t!(matches.value_of("seed"), u64) {
+            Ok(seed) => Sampling::Random { seed },
+            Err(_) => Sampling::Alphabetical,
+        };
+
+        let categories = task_index.categories().unwrap();
+        let sample = sample::sample_per_category(&categories, max, sampling);
+
+        let selected: Vec<String> = match task_names {
+            Some(ref names) => names
+                .iter()
+                .filter(|name| sample.contains(*name))
+                .cloned()
+                .collect(),
+            None => sample.into_iter().collect(),
+        };
+
+        // An empty list would fetch every task.
+        if selected.is_empty() {
+            eprintln!("No tasks were sampled.");
+            return;
+        }
+
+        task_names = Some(selected);
+    }
+
+    let since_revision = matches.value_of("since-revision");
+    let save_revisions = matches.value_of("save-revisions");
+
+    if since_revision.is_some() || save_revisions.is_some() {
+        let previous = since_revision.map(|path| match read_revisions(path) {
+            Ok(revisions) => revisions,
+            Err(err) => {
+                eprintln!("Could not read the revisions in {}: {}", path, err);
+                process::exit(2);
+            }
+        });
+
+        // Compare the tasks in the stored revisions, unless only some tasks were requested.
+        let titles = match (&previous, &task_names) {
+            (&Some(ref previous), &Some(ref names)) => names
+                .iter()
+                .filter(|name| previous.contains_key(*name))
+                .cloned()
+                .collect(),
+            (&Some(ref previous), &None) => previous.keys().cloned().collect(),
+            (&None, &Some(ref names)) => names.clone(),
+            (&None, &None) => vec![],
+        };
+
+        // An empty list would request every task.
+        if previous.is_some() && titles.is_empty() {
+            eprintln!("None of the requested tasks have a stored revision.");
+            return;
+        }
+
+        let revisions = match task_index.latest_revisions(&titles) {
+            Ok(revisions) => revisions,
+            Err(err) => {
+                eprintln!("Could not fetch the latest revisions: {}", err);
+                process::exit(2);
+            }
+        };
+
+        // The changes are part of the report if it goes to stdout, so they aren't listed too.
+        let json_to_stdout = matches.value_of("json-file") == Some("-");
+
+        let mut changed = vec![];
+        if let Some(ref previous) = previous {
+            for title in &titles {
+                let revid = revisions.get(title).cloned();
+                if revid != previous.get(title).cloned() {
+                    changed.push(json!({
+                        "title": title,
+                        "old_revid": previous[title],
+                        "revid": revid,
+                    }));
+
+                    match revid {
+                        _ if json_to_stdout => {}
+                        Some(revid) => println!("{}: {} -> {}", title, previous[title], revid),
+                        None => println!("{}: no longer on the wiki", title),
+                    }
+                }
+            }
+
+            if let Some(filename) = matches.value_of("json-file") {
+                let compact = matches.is_present("json-compact");
+                write_json(filename, &json!(changed), compact).unwrap();
+            }
+        }
+
+        if let Some(path) = save_revisions {
+            let json = serde_json::to_string_pretty(&revisions).unwrap();
+            if let Err(err) = report::write_report(path, &json) {
+                eprintln!("Could not write the revisions to {}: {}", path, err);
+                process::exit(2);
+            }
+        }
+
+        if !changed.is_empty() {
+            process::exit(1);
+        }
+
+        // Saving the revisions alone doesn't replace the listing, which may still compare
+        // against older revisions with `--diff-against`.
+        if since_revision.is_some() {
+            return;
+        }
+    }
+
+    if matches.is_present("watch") {
+        let tasks = task_index
+            .fetch_tasks(task_names.as_ref().unwrap())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        if tasks.iter().all(|task| task.local_path().is_none()) {
+            eprintln!("None of the tasks are implemented locally, so there is nothing to watch.");
+            process::exit(1);
+        }
+
+        // Restore the colors of the terminal if it's interrupted in the middle of a diff.
+        ctrlc::set_handler(move || {
+            let _ = stdout_console(color).reset();
+            process::exit(0);
+        }).unwrap();
+
+        if let Err(err) = watch(&mut *t, &tasks, &print_options) {
+            eprintln!("Could not watch the tasks: {}", err);
+            process::exit(1);
+        }
+
+        return;
+    }
+
+    // Filters that only depend on whether tasks are implemented don't need the code of the tasks,
+    // unless it is printed or used otherwise.
+    let presence_only = match filter {
+        Filter::Local | Filter::Remote | Filter::Unimplemented => true,
+        Filter::All | Filter::Diff => false,
+    };
+
+    let needs_details = DETAIL_OPTIONS
+        .iter()
+        .any(|option| matches.is_present(option))
+        || matches.value_of("diff-against") != Some("latest");
+
+    if presence_only && !needs_details {
+        let titles = task_names.clone().unwrap_or_default();
+        let mut statuses = match task_index.fetch_status(&titles) {
+            Ok(statuses) => statuses,
+            Err(err) => {
+                eprintln!("Could not check the tasks: {}", err);
+                process::exit(2);
+            }
+        };
+
+        if let Ok(sort_order) = value_t!(matches.value_of("sort"), SortOrder) {
+            statuses.sort_by(|a, b| sort_order.compare(&a.title, &b.title));
+        }
+
+        let first = value_t!(matches.value_of("first"), usize).unwrap_or(usize::MAX);
+        let oneline = matches.value_of("format") == Some("oneline");
+
+        let matching = statuses.iter().filter(|status| match filter {
+            Filter::Local => status.implemented_locally && !status.implemented_remotely,
+            Filter::Remote => !status.implemented_locally && status.implemented_remotely,
+            _ => !status.implemented_locally && !status.implemented_remotely,
+        });
+
+        for status in matching.take(first) {
+            if oneline {
+                writeln!(t, "{}", status.summary()).unwrap();
+            } else {
+                print_presence(&mut *t, status, &print_options).unwrap();
+            }
+        }
+
+        return;
+    }
+
+    let mut tasks = match task_names {
+        Some(ref names) => task_index.fetch_tasks(names),
+        None => task_index.fetch_all_tasks(),
+    };
+
+    if let Ok(sort_order) = value_t!(matches.value_of("sort"), SortOrder) {
+        tasks = tasks.with_sort_order(sort_order);
+    }
+
+    if let Some(deadline) = matches.value_of("deadline") {
+        tasks = tasks.with_deadline(Instant::now() + parse_duration(deadline).unwrap());
+    }
+
+    let patch_dir = matches.value_of("emit-patches").map(Path::new);
+
+    if let Some(dir) = patch_dir {
+        fs::create_dir_all(dir).unwrap();
+    }
+
+    let dump_dir = matches.value_of("dump-remote").map(Path::new);
+    let overwrite = matches.is_present("overwrite");
+    let mut dumped = BTreeMap::new();
+
+    if let Some(dir) = dump_dir {
+        fs::create_dir_all(dir).unwrap();
+    }
+
+    // Comparing the code of every differing task is slow, so the changed lines are only counted
+    // if they are printed.
+    let count_line_changes = print_options.diff || matches.is_present("summary");
+    let mut summary = Summary::new();
+    let mut heatmap = Heatmap::default();
+    let mut status_groups = vec![vec![]; STATUS_GROUPS.len()];
+
+    // When the JSON is written to stdout, the human-readable output is suppressed so that stdout
+    // only contains valid JSON. The heatmap and the sections replace the status of each task.
+    let json_to_stdout = matches.value_of("json-file") == Some("-");
+    let quiet = json_to_stdout || matches.is_present("heatmap") || matches.is_present("group-by");
+    let oneline = matches.value_of("format") == Some("oneline");
+
+    let columns = matches
+        .value_of("columns")
+        .map(|columns| columns.split(',').collect::<Vec<_>>());
+
+    // The categories of every task are only requested if they are shown.
+    let mut task_categories = BTreeMap::new();
+    if let Some(ref columns) = columns {
+        if columns.contains(&"category") {
+            for (category, titles) in task_index.categories().unwrap() {
+                for title in titles {
+                    task_categories
+                        .entry(title)
+                        .or_insert_with(Vec::new)
+                        .push(category.clone());
+                }
+            }
+        }
+
+        if !quiet {
+            t.attr(term::Attr::Bold).unwrap();
+            let header = columns
+                .iter()
+                .map(|&column| (column, column.to_uppercase()))
+                .collect::<Vec<_>>();
+            print_row(&mut *t, &header).unwrap();
+            t.reset().unwrap();
+        }
+    }
+
+    let mut json_fields = match matches.value_of("json-fields") {
+        Some(fields) => fields.split(',').collect::<Vec<_>>(),
+        None => JSON_FIELDS
+            .iter()
+            .cloned()
+            .filter(|&field| field != DESCRIPTION_FIELD)
+            .collect(),
+    };
+    if matches.is_present("include-description") && !json_fields.contains(&DESCRIPTION_FIELD) {
+        json_fields.push(DESCRIPTION_FIELD);
+    }
+
+    // Stop pulling tasks from the iterator once enough have matched, so that no more batches are
+    // fetched from the wiki than necessary.
+    let first = value_t!(matches.value_of("first"), usize).unwrap_or(usize::MAX);
+    let min_languages = value_t!(matches.value_of("min-languages"), usize).unwrap_or(0);
+
+    // Tasks without a ratio can't be compared, so they are excluded by either bound.
+    let min_ratio = value_t!(matches.value_of("min-ratio"), f64).ok();
+    let max_ratio = value_t!(matches.value_of("max-ratio"), f64).ok();
+    let ratio_in_bounds = |task: &Task| {
+        if min_ratio.is_none() && max_ratio.is_none() {
+            return true;
+        }
+
+        task.diff_ratio().map_or(false, |ratio| {
+            min_ratio.map_or(true, |min| ratio >= min) && max_ratio.map_or(true, |max| ratio <= max)
+        })
+    };
+
+    // The remote checksums of the tasks in an earlier report, by title.
+    let previous_checksums = matches.value_of("only-changed-checksums").map(|path| {
+        let report = report::read_report(path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| CoverageReport::from_json(&json).map_err(|err| err.to_string()));
+
+        match report {
+            Ok(report) => report
+                .tasks
+                .into_iter()
+                .filter_map(|task| Some((task.title?, task.remote_checksum)))
+                .collect::<BTreeMap<_, _>>(),
+            Err(err) => {
+                eprintln!("Could not read the report {}: {}", path, err);
+                process::exit(2);
+            }
+        }
+    });
+    let checksum_changed = |task: &Task| {
+        previous_checksums.as_ref().map_or(true, |checksums| {
+            checksums.get(&task.title()) != Some(&task.remote_checksum())
+        })
+    };
+
+    let remote_source = match RemoteSource::from_matches(&matches, &task_index) {
+        Ok(remote_source) => remote_source,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    };
+
+    let mut fetch_errors = vec![];
+
+    let paths_root = if matches.is_present("relative-paths") {
+        Some(Path::new(manifest_dir))
+    } else {
+        None
+    };
+
+    // Tasks past the maximum number of diffs are printed without their diff.
+    let max_diffs = match value_t!(matches.value_of("max-diffs"), usize) {
+        Ok(0) | Err(_) => None,
+        Ok(max_diffs) => Some(max_diffs),
+    };
+    let options_without_diff = PrintOptions {
+        diff: false,
+        ..print_options.clone()
+    };
+    let mut differing_tasks = 0;
+
+    // Local tasks that have no code on the wiki, if `--require-remote` makes them errors.
+    let require_remote = matches.is_present("require-remote");
+    let mut missing_remote = vec![];
+
+    // Tasks whose Rust section couldn't be parsed, if `--fail-on-parse-error` makes them errors.
+    let fail_on_parse_error = matches.is_present("fail-on-parse-error");
+    let mut unparseable = vec![];
+
+    let json_tasks = tasks
+        .by_ref()
+        .filter_map(|task| match task {
+            Ok(task) => Some(task),
+            Err(err) => {
+                // Errors are reported as JSON at the end if the output is JSON.
+                if !matches.is_present("json-file") {
+                    eprintln!("{}", err);
+                }
+                fetch_errors.push(err);
+                None
+            }
+        })
+        .map(|task| remote_source.apply(task))
+        .filter(|task| matches_filter(task, &filter, print_options.ignore_whitespace))
+        .filter(|task| ratio_in_bounds(task))
+        .filter(|task| checksum_changed(task))
+        .filter(|task| task.implementation_count() >= min_languages)
+        .take(first)
+        .flat_map(|task| {
+            summary.add(&task);
+            if count_line_changes && task.diff_kind() == DiffKind::Substantive {
+                // The task caches its changeset, so the diff that is printed later reuses it.
+                if let Some(changes) = task.changeset() {
+                    summary.add_line_changes(&changes);
+                }
+            }
+            heatmap.add(&task.title(), task.local_path().is_some());
+
+            if require_remote && task.is_local_only() {
+                eprintln!(
+                    "error: '{}' is implemented locally, but not on the wiki",
+                    task.title()
+                );
+                missing_remote.push(task.title());
+            }
+            if fail_on_parse_error && task.is_unparseable() {
+                eprintln!(
+                    "error: could not parse the Rust code of '{}' on the wiki",
+                    task.title()
+                );
+                unparseable.push(task.title());
+            }
+            status_groups[status_group(&task)].push(task.title());
+
+            if !quiet {
+                if let Some(ref columns) = columns {
+                    let cells = columns
+                        .iter()
+                        .map(|&column| (column, column_value(&task, column, &task_categories)))
+                        .collect::<Vec<_>>();
+                    print_row(&mut *t, &cells).unwrap();
+                } else if oneline {
+                    writeln!(t, "{}", task.summary()).unwrap();
+                } else {
+                    let mut options = &print_options;
+                    if print_options.diff && task.diff_kind() == DiffKind::Substantive {
+                        differing_tasks += 1;
+                        if max_diffs.map_or(false, |max_diffs| differing_tasks > max_diffs) {
+                            options = &options_without_diff;
+                        }
+                    }
+
+                    print_task(&mut *t, &task, options).unwrap();
+                }
+
+                if matches.is_present("missing-deps") {
+                    print_undeclared_dependencies(&mut *t, &task).unwrap();
+                }
+
+                if matches.is_present("check-main") {
+                    print_missing_main(&mut *t, &task).unwrap();
+                }
+            }
+
+            if let Some(dir) = patch_dir {
+                if is_diverged(&task, print_options.ignore_whitespace) {
+                    write_patch(dir, &task).unwrap();
+                }
+            }
+
+            if let Some(dir) = dump_dir {
+                if let Err(err) = write_remote_code(dir, &task, overwrite, &mut dumped) {
+                    eprintln!(
+                        "warning: could not write the code of '{}': {}",
+                        task.title(),
+                        err
+                    );
+                }
+            }
+
+            if matches.is_present("json-file") {
+                Some(task_json(
+                    &task,
+                    &json_fields,
+                    url_style.unwrap_or_default(),
+                    paths_root,
+                ))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if tasks.reached_deadline() {
+        eprintln!(
+            "Deadline reached, skipped {} remaining tasks",
+            tasks.remaining()
+        );
+    }
+
+    if let Some(max_diffs) = max_diffs {
+        if differing_tasks > max_diffs {
+            writeln!(
+                t,
+                "+{} more differing tasks (use --max-diffs 0 for all)",
+                differing_tasks - max_diffs
+            ).unwrap();
+        }
+    }
+
+    if print_options.diff && !json_to_stdout && summary.differing > 0 {
+        writeln!(
+            t,
+            "Across {} differing tasks: +{} added, -{} removed",
+            summary.differing, summary.lines_added, summary.lines_removed
+        ).unwrap();
+    }
+
+    if matches.is_present("heatmap") && !json_to_stdout {
+        print_heatmap(&mut *t, &heatmap).unwrap();
+    }
+
+    if let Some(ref caching_client) = caching_client {
+        if matches.is_present("verbose") {
+            eprintln!("{}", caching_client.stats());
+        }
+    }
+
+    if let Some(stats) = task_index.revision_cache_stats() {
+        if matches.is_present("verbose") {
+            eprintln!("revision {}", stats);
+        }
+    }
+
+    if matches.is_present("group-by") && !json_to_stdout {
+        print_status_groups(&mut *t, &status_groups).unwrap();
+    }
+
+    if matches.is_present("summary") && !json_to_stdout {
+        print_summary(&mut *t, &summary).unwrap();
+    }
+
+    if let Some(filename) = matches.value_of("json-file") {
+        // The summary is only included on request, so that the output stays a plain array of
+        // tasks for existing consumers.
+        let json = if matches.is_present("summary") {
+            json!({
+                "tasks": json_tasks,
+                "summary": summary,
+            })
+        } else {
+            json!(json_tasks)
+        };
+
+        write_json(filename, &json, matches.is_present("json-compact")).unwrap();
+
+        if !fetch_errors.is_empty() {
+            eprintln!("{}", errors_json(&fetch_errors));
+        }
+    }
+
+    if let Ok(threshold) = value_t!(matches.value_of("fail-under"), f64) {
+        let percent = summary.coverage_percent();
+        if percent < threshold {
+            eprintln!(
+                "Coverage is {:.2}%, which is below the threshold of {}%",
+                percent, threshold
+            );
+            process::exit(1);
+        }
+    }
+
+    if !missing_remote.is_empty() {
+        eprintln!(
+            "{} tasks are implemented locally but not on the wiki",
+            missing_remote.len()
+        );
+
+        if matches.is_present("fail-on-match") {
+            process::exit(1);
+        }
+    }
+
+    if !unparseable.is_empty() {
+        eprintln!(
+            "{} tasks have a Rust section on the wiki that could not be parsed",
+            unparseable.len()
+        );
+        process::exit(1);
+    }
+
+    if !fetch_errors.is_empty() {
+        process::exit(1);
     }
 }